@@ -0,0 +1,85 @@
+//! Serve the operations a read-only FUSE filesystem needs, using inode references as handles.
+//!
+//! A FUSE driver (e.g. built on `fuser`) hands out inode numbers, and receives them back in
+//! every request. Using the `InodeRef` of each file as that number (offset by one, since FUSE
+//! reserves 1 for the root) means no extra bookkeeping is needed:
+//!
+//! * `lookup(parent, name)` => [`Archive::lookup`]
+//! * `getattr(ino)` => `archive.open_ref(ino)?.metadata()`
+//! * `readdir(ino, offset)` => [`Archive::readdir`]
+//! * `read(ino, offset, size)` => `archive.open_ref(ino)?.read_at(offset, buf)`
+//!
+//! This example walks a path with `lookup`, then lists it with `readdir` (in small batches, as
+//! FUSE would), or reads it with `read_at` (in chunks, as FUSE would).
+
+use bstr::BStr;
+use sqsh_rs::{Archive, InodeRef};
+use std::env;
+use std::io::Write;
+
+const READ_SIZE: usize = 4096;
+const READDIR_BATCH: usize = 4;
+
+fn main() {
+    let mut args = env::args_os().skip(1);
+    let archive_path = args.next().expect("missing archive argument");
+    let path = args.next().unwrap_or_default();
+    let archive = Archive::new(archive_path).unwrap();
+
+    let mut inode_ref = archive.superblock().root_inode_ref();
+    for name in path.as_encoded_bytes().split(|&b| b == b'/') {
+        if name.is_empty() {
+            continue;
+        }
+        match archive.lookup(inode_ref, name).unwrap() {
+            Some((child_ref, _)) => inode_ref = child_ref,
+            None => panic!("no such file: {}", BStr::new(name)),
+        }
+    }
+
+    let metadata = archive.open_ref(inode_ref).unwrap().metadata();
+    if metadata.file_type() == Some(sqsh_rs::FileType::Directory) {
+        list(&archive, inode_ref);
+    } else {
+        read(&archive, inode_ref);
+    }
+}
+
+fn list(archive: &Archive<'_>, dir_ref: InodeRef) {
+    let mut offset = 0;
+    loop {
+        let mut dir = archive.readdir(dir_ref, offset).unwrap();
+        let mut count = 0;
+        while count < READDIR_BATCH {
+            let Some(entry) = dir.advance().unwrap() else {
+                break;
+            };
+            count += 1;
+            println!(
+                "{:>4} {:?} {}",
+                offset + count as u64,
+                entry.inode_ref(),
+                entry.name()
+            );
+        }
+        if count < READDIR_BATCH {
+            break;
+        }
+        offset += count as u64;
+    }
+}
+
+fn read(archive: &Archive<'_>, file_ref: InodeRef) {
+    let file = archive.open_ref(file_ref).unwrap();
+    let mut stdout = std::io::stdout().lock();
+    let mut buf = [0; READ_SIZE];
+    let mut offset = 0;
+    loop {
+        let n = file.read_at(offset, &mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        stdout.write_all(&buf[..n]).unwrap();
+        offset += n as u64;
+    }
+}
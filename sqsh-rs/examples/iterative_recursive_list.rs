@@ -18,7 +18,7 @@ fn main() {
         );
 
         if file.file_type() == Some(sqsh_rs::FileType::Directory) {
-            let mut iter = file.as_dir().unwrap();
+            let mut iter = archive.read_dir_ref(inode_ref).unwrap();
             while let Some(entry) = iter.advance().unwrap() {
                 let name = entry.name();
                 let mut path = path.clone();
@@ -11,6 +11,14 @@ fn main() {
     let mut iterator = file.as_dir().unwrap();
     while let Some(entry) = iterator.advance().unwrap() {
         let name = entry.name();
-        println!("{}", BStr::new(name));
+        let metadata = entry.open_metadata().unwrap();
+        println!(
+            "{} {:>6} {:>6} {:>10} {}",
+            metadata.permissions(),
+            metadata.uid(),
+            metadata.gid(),
+            metadata.size(),
+            BStr::new(name)
+        );
     }
 }
@@ -12,6 +12,6 @@ fn main() {
             continue;
         }
 
-        println!("/{}", entry.path());
+        println!("{} /{}", entry.type_char(), entry.path());
     }
 }
@@ -1,24 +1,55 @@
-use crate::source::SourceVtable;
+#[cfg(unix)]
+use crate::source::FdSource;
+use crate::source::{OwnedSource, SourceVtable};
 use crate::utils::small_c_string::run_with_cstr;
-use crate::{error, File, Source};
+use crate::{error, DirectoryIterator, Error, File, Source};
 use sqsh_sys as ffi;
 use sqsh_sys::SqshMemoryMapperImpl;
-use std::ffi::c_void;
+use std::ffi::{c_int, c_void};
 use std::marker::PhantomData;
 use std::mem;
+#[cfg(unix)]
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
 use std::path::Path;
 use std::ptr::NonNull;
+use std::sync::{Arc, OnceLock};
 
 /// A squashfs filesystem archive.
-#[derive(Debug)]
+///
+/// Opening an archive only reads and validates the superblock, and sets up the decompressor.
+/// Everything else (the id, export, fragment and xattr tables, the inode map, and compression
+/// options) is only read the first time it is needed, so opening an archive to inspect its
+/// superblock is cheap, and there is no need for a separate "lazy" way to open an archive.
+///
+/// Cloning an archive is cheap: clones are handles to the same underlying archive, sharing its
+/// caches, which is closed when the last handle is dropped.
+#[derive(Debug, Clone)]
 pub struct Archive<'a> {
-    pub(crate) inner: NonNull<ffi::SqshArchive>,
+    pub(crate) inner: Arc<RawArchive>,
     _marker: PhantomData<&'a ()>,
 }
 
+// The archive shared by all clones of an `Archive`
+#[derive(Debug)]
+pub(crate) struct RawArchive {
+    archive: NonNull<ffi::SqshArchive>,
+    // Cached result of `total_uncompressed_size`
+    pub(crate) total_uncompressed_size: OnceLock<u64>,
+    // Whether opening a path follows a symlink at the end of the path
+    pub(crate) follow_symlinks: bool,
+    // The size of the source when the archive was opened
+    pub(crate) source_size: u64,
+}
+
+impl RawArchive {
+    pub(crate) fn as_ptr(&self) -> *mut ffi::SqshArchive {
+        self.archive.as_ptr()
+    }
+}
+
 // Safety: SqshArchive is uses a mutex internally for thread safety
-unsafe impl<'a> Send for Archive<'a> {}
-unsafe impl<'a> Sync for Archive<'a> {}
+unsafe impl Send for RawArchive {}
+unsafe impl Sync for RawArchive {}
 
 /// Ways to create an archive.
 impl<'a> Archive<'a> {
@@ -27,62 +58,274 @@ impl<'a> Archive<'a> {
     where
         P: AsRef<Path>,
     {
-        Self::_new(path.as_ref())
+        ArchiveBuilder::new().open(path)
     }
 
-    fn _new(path: &Path) -> error::Result<Self> {
-        run_with_cstr(path.as_os_str().as_encoded_bytes(), |path| unsafe {
-            Self::new_raw_simple(&*ffi::sqsh_mapper_impl_mmap, 0, path.as_ptr().cast())
-        })
+    /// Open a squashfs archive which starts `back_offset` bytes before the end of a file.
+    ///
+    /// This supports formats which append a squashfs image to another file, where only the
+    /// distance of the image from the end of the file is known. The absolute offset is computed
+    /// from the current size of the file, see [`ArchiveBuilder::offset`].
+    pub fn new_with_offset_from_end<P>(path: P, back_offset: u64) -> error::Result<Archive<'static>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let len = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => return Err(error::new(e.raw_os_error().unwrap_or(libc::EIO))),
+        };
+        let Some(offset) = len.checked_sub(back_offset) else {
+            return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS));
+        };
+        ArchiveBuilder::new().offset(offset).open(path)
     }
 
     /// Open a squashfs archive from a slice of data.
     pub fn from_slice(data: &'a [u8]) -> error::Result<Self> {
-        unsafe {
-            Self::new_raw_simple(
-                &*ffi::sqsh_mapper_impl_static,
-                data.len(),
-                data.as_ptr().cast(),
-            )
-        }
+        ArchiveBuilder::new().open_slice(data)
+    }
+
+    /// Open a squashfs archive from an owned buffer of data.
+    ///
+    /// Unlike [`Archive::from_slice`], the returned archive takes ownership of the data, so it
+    /// does not borrow from the caller.
+    pub fn from_vec(data: Vec<u8>) -> error::Result<Archive<'static>> {
+        Archive::with_source(OwnedSource::new(data))
+    }
+
+    /// Open a squashfs archive from a shared, reference counted buffer of data.
+    ///
+    /// The archive keeps a clone of the `Arc`, so the same buffer can back any number of
+    /// archives (or be used elsewhere) without being copied.
+    pub fn from_shared_bytes(data: Arc<[u8]>) -> error::Result<Archive<'static>> {
+        Archive::with_source(OwnedSource::new(data))
+    }
+
+    /// Open a squashfs archive from an open file descriptor.
+    ///
+    /// The archive is read with positioned reads (`pread`), so the file offset of the descriptor
+    /// is not used or changed. The archive takes ownership of the descriptor, and closes it when
+    /// dropped.
+    #[cfg(unix)]
+    pub fn from_fd(fd: impl Into<OwnedFd>) -> error::Result<Archive<'static>> {
+        Archive::with_source(FdSource::new(fd.into()))
+    }
+
+    /// Open a squashfs archive from a raw file descriptor, taking ownership of it.
+    ///
+    /// See [`Archive::from_fd`].
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor, which is not owned by anything else: it will be
+    /// closed when the archive is dropped.
+    #[cfg(unix)]
+    pub unsafe fn from_raw_fd(fd: RawFd) -> error::Result<Archive<'static>> {
+        Archive::from_fd(OwnedFd::from_raw_fd(fd))
+    }
+
+    /// Open a squashfs archive from a custom source.
+    pub fn with_source<S: Source + 'a>(source: S) -> error::Result<Self> {
+        ArchiveBuilder::new().open_source(source)
     }
 
-    unsafe fn new_raw(config: &ffi::SqshConfig, source_ptr: *const c_void) -> error::Result<Self> {
+    /// Returns a builder, to open an archive with non-default options.
+    #[must_use]
+    pub fn builder() -> ArchiveBuilder {
+        ArchiveBuilder::new()
+    }
+
+    unsafe fn new_raw(
+        config: &ffi::SqshConfig,
+        source_ptr: *const c_void,
+        follow_symlinks: bool,
+        source_size: u64,
+    ) -> error::Result<Self> {
         let mut err = 0;
         let archive = ffi::sqsh_archive_open(source_ptr, config, &mut err);
 
         match NonNull::new(archive) {
             Some(archive) => Ok(Self {
-                inner: archive,
+                inner: Arc::new(RawArchive {
+                    archive,
+                    total_uncompressed_size: OnceLock::new(),
+                    follow_symlinks,
+                    source_size,
+                }),
                 _marker: PhantomData,
             }),
             None => Err(error::new(err)),
         }
     }
 
-    unsafe fn new_raw_simple(
+    unsafe fn new_raw_with(
+        options: &ArchiveBuilder,
         source_mapper: &'a SqshMemoryMapperImpl,
         size: usize,
+        source_size: u64,
         source_ptr: *const c_void,
     ) -> error::Result<Self> {
         let config = ffi::SqshConfig {
-            archive_offset: 0,
+            archive_offset: options.archive_offset,
             source_size: size.try_into().unwrap(),
             source_mapper,
-            mapper_block_size: 0,
-            mapper_lru_size: 0,
-            compression_lru_size: 0,
-            max_symlink_depth: 0,
+            mapper_block_size: options.mapper_block_size,
+            mapper_lru_size: options.mapper_lru_size,
+            compression_lru_size: options.compression_lru_size,
+            max_symlink_depth: options.max_symlink_depth,
             _reserved: unsafe { mem::zeroed() },
         };
-        Self::new_raw(&config, source_ptr)
+        let archive = Self::new_raw(&config, source_ptr, !options.nofollow_symlinks, source_size)?;
+        if let Some((major, minor)) = options.required_version {
+            let superblock = archive.superblock();
+            if superblock.version_major() != major || superblock.version_minor() > minor {
+                return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_UNSUPPORTED_VERSION));
+            }
+        }
+        Ok(archive)
+    }
+}
+
+/// Options for opening an archive.
+///
+/// Created with [`Archive::builder`]. Any options which are not set use the libsqsh defaults.
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveBuilder {
+    archive_offset: u64,
+    mapper_block_size: c_int,
+    mapper_lru_size: c_int,
+    compression_lru_size: c_int,
+    max_symlink_depth: usize,
+    required_version: Option<(u16, u16)>,
+    nofollow_symlinks: bool,
+}
+
+impl ArchiveBuilder {
+    /// Create a builder with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the byte offset in the source where the archive begins.
+    pub fn offset(&mut self, offset: u64) -> &mut Self {
+        self.archive_offset = offset;
+        self
+    }
+
+    /// Set the size of chunks which are retrieved from the source at once.
+    ///
+    /// By default, the block size is chosen by the source.
+    pub fn mapper_block_size(&mut self, size: u32) -> &mut Self {
+        self.mapper_block_size = c_int::try_from(size).unwrap_or(c_int::MAX);
+        self
+    }
+
+    /// Set the number of chunks from the source to keep cached.
+    ///
+    /// A size of 0 disables the cache.
+    pub fn mapper_lru_size(&mut self, size: u32) -> &mut Self {
+        self.mapper_lru_size = lru_size(size);
+        self
+    }
+
+    /// Set the number of decompressed blocks to keep cached.
+    ///
+    /// A size of 0 disables the cache.
+    pub fn compression_lru_size(&mut self, size: u32) -> &mut Self {
+        self.compression_lru_size = lru_size(size);
+        self
+    }
+
+    /// Set the maximum number of symlinks which will be followed when resolving a path.
+    ///
+    /// When the limit is exceeded, a "too many symlinks followed" error is returned. This bounds
+    /// the cost of resolving paths in untrusted archives. A depth of 0 uses the default of 100.
+    pub fn max_symlink_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_symlink_depth = depth;
+        self
+    }
+
+    /// Set whether opening a path follows a symlink at the end of the path.
+    ///
+    /// When disabled, [`Archive::open`] behaves like [`Archive::open_nofollow`], and so do
+    /// the methods built on it, like [`Archive::read`] and [`Archive::read_dir`]: a symlink is
+    /// returned as an opaque leaf, rather than being resolved to its target. Traversals never
+    /// follow symlinks unless enabled with [`Traversal::set_follow_symlinks`].
+    ///
+    /// Disabling this is recommended when handling untrusted archives, e.g. when extracting
+    /// them, so a symlink can't redirect an access to an unexpected file.
+    ///
+    /// Enabled by default.
+    ///
+    /// [`Traversal::set_follow_symlinks`]: crate::traverse::Traversal::set_follow_symlinks
+    pub fn follow_symlinks(&mut self, follow: bool) -> &mut Self {
+        self.nofollow_symlinks = !follow;
+        self
+    }
+
+    /// Require the archive to have a supported squashfs format version.
+    ///
+    /// Opening an archive fails with an "unsupported version" error unless its major version is
+    /// `major`, and its minor version is at most `minor`. This allows rejecting archives in a
+    /// format newer than the caller can handle up front, rather than failing later with
+    /// confusing corruption errors.
+    pub fn require_version(&mut self, major: u16, minor: u16) -> &mut Self {
+        self.required_version = Some((major, minor));
+        self
+    }
+
+    /// Open a squashfs archive from a file.
+    pub fn open<P>(&self, path: P) -> error::Result<Archive<'static>>
+    where
+        P: AsRef<Path>,
+    {
+        self._open(path.as_ref())
+    }
+
+    fn _open(&self, path: &Path) -> error::Result<Archive<'static>> {
+        // If the file can't be stat-ed, mapping it will fail with a more specific error
+        let source_size = std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+        run_with_cstr(path.as_os_str().as_encoded_bytes(), |path| unsafe {
+            Archive::new_raw_with(
+                self,
+                &*ffi::sqsh_mapper_impl_mmap,
+                0,
+                source_size,
+                path.as_ptr().cast(),
+            )
+        })
+    }
+
+    /// Open a squashfs archive from a slice of data.
+    pub fn open_slice<'a>(&self, data: &'a [u8]) -> error::Result<Archive<'a>> {
+        unsafe {
+            Archive::new_raw_with(
+                self,
+                &*ffi::sqsh_mapper_impl_static,
+                data.len(),
+                data.len() as u64,
+                data.as_ptr().cast(),
+            )
+        }
     }
 
     /// Open a squashfs archive from a custom source.
-    pub fn with_source<S: Source + 'a>(source: S) -> error::Result<Self> {
+    pub fn open_source<'a, S: Source + 'a>(&self, mut source: S) -> error::Result<Archive<'a>> {
         let vtable: &'a SourceVtable<S> = &const { SourceVtable::new() };
+        let source_size = source.size()? as u64;
         let source_ptr = crate::source::to_ptr(source);
-        unsafe { Self::new_raw_simple(vtable.mapper_impl(), 0, source_ptr) }
+        unsafe { Archive::new_raw_with(self, vtable.mapper_impl(), 0, source_size, source_ptr) }
+    }
+}
+
+// libsqsh uses 0 for the default size, and -1 to disable the cache
+fn lru_size(size: u32) -> c_int {
+    if size == 0 {
+        -1
+    } else {
+        c_int::try_from(size).unwrap_or(c_int::MAX)
     }
 }
 
@@ -93,12 +336,31 @@ impl<'a> Archive<'a> {
         let inode_ref = superblock.root_inode_ref();
         self.open_ref(inode_ref)
     }
+
+    /// Returns the size of the source the archive was opened from, in bytes.
+    ///
+    /// This is the size of the whole file, buffer or custom [`Source`] when the archive was
+    /// opened, including any data before the archive's [offset](ArchiveBuilder::offset), and
+    /// any padding after it. Unlike [`Archive::bytes_used`], which is recorded in the archive
+    /// itself, this can be used to detect a truncated archive: the archive is complete only if
+    /// its offset plus `bytes_used` is at most `source_size`.
+    ///
+    /// Changes to the size of a file after the archive was opened are not reflected.
+    #[must_use]
+    pub fn source_size(&self) -> u64 {
+        self.inner.source_size
+    }
+
+    /// List the root directory of the archive.
+    pub fn read_root_dir(&self) -> error::Result<DirectoryIterator<'_, '_>> {
+        self.root()?.into_dir()
+    }
 }
 
-impl Drop for Archive<'_> {
+impl Drop for RawArchive {
     fn drop(&mut self) {
         unsafe {
-            ffi::sqsh_archive_close(self.inner.as_ptr());
+            ffi::sqsh_archive_close(self.archive.as_ptr());
         }
     }
 }
@@ -0,0 +1,116 @@
+use crate::Archive;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// A pool of reusable byte buffers.
+///
+/// When reading many files (e.g. extracting an archive from several threads at once),
+/// allocating a new buffer for every file can be a significant cost. A pool hands out buffers
+/// with [`BufferPool::get`], which are returned to the pool when dropped, to be reused by the
+/// next caller. The pool can be shared between threads.
+///
+/// Buffers are handed out empty, but with at least the pool's buffer size of capacity. They pair
+/// well with [`File::read_into`](crate::File::read_into), which reuses the capacity of the
+/// buffer.
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    buffer_size: usize,
+}
+
+impl BufferPool {
+    /// Create a new, empty pool, handing out buffers of at least `buffer_size` bytes.
+    #[must_use]
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+            buffer_size,
+        }
+    }
+
+    /// Create a new, empty pool, handing out buffers of the block size of the archive.
+    #[must_use]
+    pub fn for_archive(archive: &Archive<'_>) -> Self {
+        Self::new(archive.block_size().try_into().unwrap())
+    }
+
+    /// Returns the minimum capacity of buffers handed out by the pool.
+    #[must_use]
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Returns the number of idle buffers currently held by the pool.
+    #[must_use]
+    pub fn idle_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Take an empty buffer from the pool, or allocate a new one if the pool is empty.
+    ///
+    /// The buffer is returned to the pool when the returned guard is dropped.
+    #[must_use]
+    pub fn get(&self) -> PooledBuffer<'_> {
+        let buf = match self.lock().pop() {
+            Some(buf) => buf,
+            None => Vec::with_capacity(self.buffer_size),
+        };
+        PooledBuffer { buf, pool: self }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        // The buffers are always left in a valid state, so poisoning can be ignored
+        self.buffers
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("buffer_size", &self.buffer_size)
+            .field("idle_count", &self.idle_count())
+            .finish()
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`].
+///
+/// Dereferences to the underlying `Vec<u8>`. When dropped, the buffer is cleared and returned
+/// to the pool.
+pub struct PooledBuffer<'pool> {
+    buf: Vec<u8>,
+    pool: &'pool BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+impl fmt::Debug for PooledBuffer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.buf.len())
+            .field("capacity", &self.buf.capacity())
+            .finish()
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        self.pool.lock().push(buf);
+    }
+}
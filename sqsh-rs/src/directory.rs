@@ -1,5 +1,5 @@
-use crate::{error, File, FileType, Inode, InodeRef};
-use bstr::BStr;
+use crate::{error, File, FileType, Inode, InodeRef, Metadata};
+use bstr::{BStr, BString};
 use sqsh_sys as ffi;
 use std::ffi::c_char;
 use std::fmt;
@@ -7,6 +7,9 @@ use std::ptr::NonNull;
 
 pub struct DirectoryIterator<'file, 'archive> {
     inner: NonNull<ffi::SqshDirectoryIterator>,
+    // Keeps the directory alive for iterators created with `File::into_dir`.
+    // Must be dropped after `inner` is freed.
+    _file: Option<File<'archive>>,
     _marker: std::marker::PhantomData<&'file File<'archive>>,
 }
 
@@ -22,6 +25,18 @@ impl<'file, 'archive> DirectoryIterator<'file, 'archive> {
     pub(crate) unsafe fn new(inner: NonNull<ffi::SqshDirectoryIterator>) -> Self {
         Self {
             inner,
+            _file: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub(crate) unsafe fn new_owned(
+        inner: NonNull<ffi::SqshDirectoryIterator>,
+        file: File<'archive>,
+    ) -> Self {
+        Self {
+            inner,
+            _file: Some(file),
             _marker: std::marker::PhantomData,
         }
     }
@@ -56,13 +71,25 @@ impl<'file, 'archive> DirectoryIterator<'file, 'archive> {
             Ok(Some(unsafe { DirectoryEntry::new(self.inner.as_ref()) }))
         } else {
             let err = error::new(err);
-            if err == error::Error(ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE) {
+            if err == error::Error::Sqsh(ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE) {
                 Ok(None)
             } else {
                 Err(err)
             }
         }
     }
+
+    /// Collects all remaining entries, sorted by name.
+    ///
+    /// Entries are usually stored sorted by name already, this guarantees the order regardless.
+    pub fn collect_sorted(mut self) -> error::Result<Vec<OwnedDirectoryEntry>> {
+        let mut entries = Vec::new();
+        while let Some(entry) = self.advance()? {
+            entries.push(OwnedDirectoryEntry::from(entry));
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
 }
 
 impl Drop for DirectoryIterator<'_, '_> {
@@ -108,6 +135,10 @@ impl<'dir, 'archive> DirectoryEntry<'dir, 'archive> {
     }
 
     /// Open the current entry as a file.
+    ///
+    /// This opens the inode of the entry itself, symlinks are never followed (like
+    /// [`Archive::open_nofollow`](crate::Archive::open_nofollow)). Use
+    /// [`File::follow_all_symlinks`] to resolve a symlink to its target.
     pub fn open(&self) -> error::Result<File<'archive>> {
         let mut err = 0;
         let file = unsafe { ffi::sqsh_directory_iterator_open_file(self.inner, &mut err) };
@@ -117,6 +148,98 @@ impl<'dir, 'archive> DirectoryEntry<'dir, 'archive> {
         };
         Ok(unsafe { File::new(file) })
     }
+
+    /// Open the current entry, and return a snapshot of its metadata.
+    ///
+    /// This is a shortcut for `entry.open()?.metadata()`, for when the `File` itself isn't needed.
+    pub fn open_metadata(&self) -> error::Result<Metadata> {
+        Ok(self.open()?.metadata())
+    }
+
+    // TODO: index/offset accessors (and a matching DirectoryIterator::advance_to_offset), once
+    //       libsqsh exposes the position of an entry within the directory table
+}
+
+/// An owned copy of a [`DirectoryEntry`].
+///
+/// Unlike `DirectoryEntry`, this does not borrow from the directory iterator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OwnedDirectoryEntry {
+    name: BString,
+    file_type: Option<FileType>,
+    inode: Inode,
+    inode_ref: InodeRef,
+}
+
+impl OwnedDirectoryEntry {
+    /// Retrieves the file type of the entry.
+    #[must_use]
+    pub fn file_type(&self) -> Option<FileType> {
+        self.file_type
+    }
+
+    /// Retrieves the name of the entry.
+    #[must_use]
+    pub fn name(&self) -> &BStr {
+        self.name.as_ref()
+    }
+
+    /// Retrieves the inode number of the entry.
+    #[must_use]
+    pub fn inode(&self) -> Inode {
+        self.inode
+    }
+
+    /// Retrieves the inode ref of the entry.
+    ///
+    /// This can be used to open the entry with [`Archive::open_ref`](crate::Archive::open_ref).
+    #[must_use]
+    pub fn inode_ref(&self) -> InodeRef {
+        self.inode_ref
+    }
+}
+
+impl From<DirectoryEntry<'_, '_>> for OwnedDirectoryEntry {
+    fn from(entry: DirectoryEntry<'_, '_>) -> Self {
+        Self {
+            name: entry.name().to_owned(),
+            file_type: entry.file_type(),
+            inode: entry.inode(),
+            inode_ref: entry.inode_ref(),
+        }
+    }
+}
+
+/// The name and metadata of an entry in a directory.
+///
+/// Returned by [`Archive::list_dir`](crate::Archive::list_dir).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DirEntryInfo {
+    name: BString,
+    metadata: Metadata,
+}
+
+impl DirEntryInfo {
+    pub(crate) fn new(entry: &DirectoryEntry<'_, '_>) -> error::Result<Self> {
+        Ok(Self {
+            name: entry.name().to_owned(),
+            metadata: entry.open_metadata()?,
+        })
+    }
+
+    /// Retrieves the name of the entry.
+    #[must_use]
+    pub fn name(&self) -> &BStr {
+        self.name.as_ref()
+    }
+
+    /// Retrieves the metadata of the entry.
+    ///
+    /// This includes the file type, size, owner, permissions, and modification time.
+    #[must_use]
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
 }
 
 impl fmt::Debug for DirectoryEntry<'_, '_> {
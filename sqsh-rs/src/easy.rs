@@ -1,32 +1,288 @@
+use bstr::{BString, ByteSlice};
 use sqsh_sys as ffi;
+use std::collections::{BTreeSet, HashSet};
 use std::io::BufRead;
 use std::ptr;
 
 use crate::utils::small_c_string::run_with_cstr;
-use crate::{error, Archive, Error, Permissions};
+use crate::{error, Archive, DirEntryInfo, Error, File, FileType, InodeRef, Metadata, Permissions};
 
 /// High level "easy" methods for interacting with the archive.
 impl Archive<'_> {
     /// Read the file at the given path
+    ///
+    /// Symlinks are followed, see [`Archive::read_nofollow`] to read the symlink itself.
     pub fn read(&self, path: &str) -> error::Result<Vec<u8>> {
         let file = self.open(path)?;
-        let mut reader = file.reader()?;
-        let size = match usize::try_from(file.size()) {
-            Ok(size) => size,
-            Err(_) => return Err(Error(ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW)),
-        };
+        read_capped(&file, usize::MAX)
+    }
+
+    /// Read the file with the given inode reference
+    ///
+    /// This is the same as [`Archive::read`], for callers which already have an inode reference
+    /// (e.g. from a traversal or an [`InodeMap`](crate::InodeMap)), and avoids resolving a path
+    /// again. Like [`Archive::open_ref`], symlinks are never followed.
+    pub fn read_ref(&self, inode_ref: InodeRef) -> error::Result<Vec<u8>> {
+        let file = self.open_ref(inode_ref)?;
+        read_capped(&file, usize::MAX)
+    }
+
+    /// Read the file at the given path, or return `default()` if it does not exist
+    ///
+    /// Only a missing file is replaced with the default: any other error (e.g. a corrupted
+    /// archive) is returned. See [`Archive::read_or_default`] to fall back on any error.
+    pub fn try_read_or_default(
+        &self,
+        path: &str,
+        default: impl FnOnce() -> Vec<u8>,
+    ) -> error::Result<Vec<u8>> {
+        match self.read(path) {
+            Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE)) => Ok(default()),
+            result => result,
+        }
+    }
+
+    /// Read the file at the given path, or return `default()` if it cannot be read
+    ///
+    /// This is convenient for loading optional files, like configuration. Any error (e.g. a
+    /// missing file, a directory, or a corrupted archive) is replaced with the default, see
+    /// [`Archive::try_read_or_default`] to only fall back for missing files.
+    pub fn read_or_default(&self, path: &str, default: impl FnOnce() -> Vec<u8>) -> Vec<u8> {
+        self.read(path).unwrap_or_else(|_| default())
+    }
 
-        let mut dst = Vec::with_capacity(size);
+    /// Read the file at the given path, without following symlinks
+    ///
+    /// If the path refers to a symlink, the contents of the symlink are returned: this is the
+    /// target of the link, the same as [`File::symlink_path`]. Otherwise, this is the same as
+    /// [`Archive::read`].
+    pub fn read_nofollow(&self, path: &str) -> error::Result<Vec<u8>> {
+        let file = self.open_nofollow(path)?;
+        match file.symlink_path() {
+            Some(target) => Ok(target.to_vec()),
+            None => read_capped(&file, usize::MAX),
+        }
+    }
+
+    /// Read the file at the given path, failing if it is larger than `max` bytes
+    ///
+    /// This can be used to bound the memory used when reading from untrusted archives. The
+    /// contents are never truncated: if the file is larger than `max` bytes, an "out of bounds"
+    /// error is returned instead. The size of the file is checked before anything is read, so
+    /// no more than `max` bytes will ever be allocated.
+    pub fn read_capped(&self, path: &str, max: usize) -> error::Result<Vec<u8>> {
+        let file = self.open(path)?;
+        read_capped(&file, max)
+    }
+
+    /// Read the file at the given path, discarding the contents
+    ///
+    /// This populates the archive's internal caches of mapped and decompressed blocks, so
+    /// later reads of the file can avoid paying the cost of decompression. Note that the caches
+    /// are LRU caches of a limited size: prefetching a file larger than the caches, or prefetching
+    /// many files, will evict previously cached blocks.
+    pub fn prefetch(&self, path: &str) -> error::Result<()> {
+        let file = self.open(path)?;
+        let mut reader = file.reader()?;
         loop {
-            let buf = reader.fill_buf_raw()?;
-            if buf.is_empty() {
+            let len = reader.fill_buf_raw()?.len();
+            if len == 0 {
                 break;
             }
-            dst.extend_from_slice(buf);
-            let len = buf.len();
             reader.consume(len);
         }
-        Ok(dst)
+        Ok(())
+    }
+
+    /// Count the entries under the given path, including the path itself
+    ///
+    /// This counts every entry which a traversal of `path` will visit, skipping the second visit
+    /// of each directory, so it can be used for progress reporting before traversing the same
+    /// path. Note that this requires a full walk of the tree.
+    pub fn count_entries(&self, path: &str) -> error::Result<usize> {
+        let file = self.open(path)?;
+        let mut traversal = file.traversal()?;
+        let mut count = 0;
+        while let Some(entry) = traversal.advance()? {
+            if !entry.state().is_second_visit() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// List every entry in the archive, with its inode reference, sorted by inode reference
+    ///
+    /// Inodes are stored roughly in the same order as the data of the files they describe, so
+    /// processing files in this order (e.g. when extracting the whole archive) keeps reads from
+    /// the archive mostly sequential. The root directory is included, with an empty path.
+    ///
+    /// Note that this walks the whole tree and collects the path of every entry up front, so
+    /// memory use is proportional to the total length of all paths in the archive.
+    pub fn entries_by_inode_order(&self) -> error::Result<Vec<(BString, InodeRef)>> {
+        let root = self.root()?;
+        let root_ref = root.inode_ref();
+        let mut traversal = root.traversal()?;
+        let mut entries = Vec::new();
+        while let Some(entry) = traversal.advance()? {
+            if entry.state().is_second_visit() {
+                continue;
+            }
+            let inode_ref = match entry.directory_entry() {
+                Some(dir_entry) => dir_entry.inode_ref(),
+                None => root_ref,
+            };
+            entries.push((entry.path_buf(), inode_ref));
+        }
+        // Stable sort: hard links to the same inode stay in traversal order
+        entries.sort_by_key(|&(_, inode_ref)| inode_ref);
+        Ok(entries)
+    }
+
+    /// Find every entry in the archive with the extended attribute `full_name`
+    ///
+    /// `full_name` includes the namespace prefix, e.g. `b"security.capability"`. Entries are
+    /// returned in traversal order, the root directory has an empty path. Only the inodes of
+    /// entries with extended attributes need to be looked up in the xattr table.
+    pub fn find_with_xattr(&self, full_name: &[u8]) -> error::Result<Vec<BString>> {
+        let root = self.root()?;
+        let mut traversal = root.traversal()?;
+        let mut paths = Vec::new();
+        while let Some(entry) = traversal.advance()? {
+            if entry.state().is_second_visit() {
+                continue;
+            }
+            let file = entry.open()?;
+            if !file.has_xattrs() {
+                continue;
+            }
+            let mut xattrs = file.xattrs()?;
+            while let Some(xattr) = xattrs.advance()? {
+                if full_name.strip_prefix(xattr.prefix().as_bytes())
+                    == Some(xattr.name().as_bytes())
+                {
+                    paths.push(entry.path_buf());
+                    break;
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    /// List every entry in the archive with its metadata, in traversal order
+    ///
+    /// Paths are relative to the root, separated by `/`, and are the raw bytes stored in the
+    /// archive. The root directory is included, with an empty path. Each entry is opened once to
+    /// read its metadata, symlinks are not followed.
+    ///
+    /// This collects the whole archive up front, see [`Archive::walk_files`] to stream entries
+    /// instead.
+    pub fn index(&self) -> error::Result<Vec<(BString, Metadata)>> {
+        let root = self.root()?;
+        let mut traversal = root.traversal()?;
+        let mut entries = Vec::new();
+        while let Some(entry) = traversal.advance()? {
+            if entry.state().is_second_visit() {
+                continue;
+            }
+            let file = entry.open()?;
+            entries.push((entry.path_buf(), file.metadata()));
+        }
+        Ok(entries)
+    }
+
+    /// Find the file whose data is stored at the given byte offset in the archive
+    ///
+    /// `archive_offset` is relative to the start of the archive (the superblock). This walks
+    /// every regular file, comparing `archive_offset` against [`File::data_range`], so it is
+    /// slow for large archives, but can help diagnose corruption reported at a specific offset.
+    ///
+    /// Returns `None` if no file has a data block at the offset, e.g. for offsets in metadata
+    /// or in fragment blocks, which may be shared by several files. If the file has several hard
+    /// links, the first path in traversal order is returned.
+    pub fn file_at_offset(&self, archive_offset: u64) -> error::Result<Option<BString>> {
+        let root = self.root()?;
+        let mut traversal = root.traversal()?;
+        while let Some(entry) = traversal.advance()? {
+            if entry.file_type() != FileType::File {
+                continue;
+            }
+            let file = entry.open()?;
+            if file
+                .data_range()
+                .is_some_and(|range| range.contains(&archive_offset))
+            {
+                return Ok(Some(entry.path_buf()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Collect the distinct `(uid, gid)` pairs of all entries in the archive
+    ///
+    /// This requires a full walk of the tree, opening every entry. If only the set of ids is
+    /// needed, rather than how they are paired, [`IdTable::iter`](crate::IdTable::iter) lists
+    /// every id used in the archive without walking it.
+    pub fn used_ids(&self) -> error::Result<BTreeSet<(u32, u32)>> {
+        let root = self.root()?;
+        let mut traversal = root.traversal()?;
+        traversal.set_visit_directories_once(true);
+        let mut ids = BTreeSet::new();
+        while let Some(entry) = traversal.advance()? {
+            let file = entry.open()?;
+            ids.insert((file.uid(), file.gid()));
+        }
+        Ok(ids)
+    }
+
+    /// List the immediate children of the directory at the given path, with their metadata
+    ///
+    /// Each child is opened once to read its metadata. Symlinks to directories are followed for
+    /// `path`, but the entries themselves are never followed.
+    pub fn list_dir(&self, path: &str) -> error::Result<Vec<DirEntryInfo>> {
+        let mut dir = self.read_dir(path)?;
+        let mut entries = Vec::new();
+        while let Some(entry) = dir.advance()? {
+            entries.push(DirEntryInfo::new(&entry)?);
+        }
+        Ok(entries)
+    }
+
+    /// Returns the total size of all regular files in the archive
+    ///
+    /// This is the space needed to extract the whole archive (not counting directories and
+    /// other metadata), as opposed to [`Archive::bytes_used`], the size of the compressed
+    /// archive. Hard links are only counted once.
+    ///
+    /// This requires a full walk of the tree the first time it is called, the result is cached
+    /// for later calls.
+    pub fn total_uncompressed_size(&self) -> error::Result<u64> {
+        if let Some(&size) = self.inner.total_uncompressed_size.get() {
+            return Ok(size);
+        }
+        let root = self.root()?;
+        let mut traversal = root.traversal()?;
+        let mut linked = HashSet::new();
+        let mut total = 0;
+        while let Some(entry) = traversal.advance()? {
+            if entry.file_type() != FileType::File {
+                continue;
+            }
+            let file = entry.open()?;
+            if file.hard_link_count() > 1 && !linked.insert(file.inode()) {
+                continue;
+            }
+            total += file.size();
+        }
+        Ok(*self.inner.total_uncompressed_size.get_or_init(|| total))
+    }
+
+    /// Read the target of the symlink at the given path
+    ///
+    /// Returns `None` if the file at the given path is not a symlink.
+    pub fn symlink_target(&self, path: &str) -> error::Result<Option<BString>> {
+        let file = self.open_nofollow(path)?;
+        Ok(file.symlink_path().map(BString::from))
     }
 
     /// Check if anything exists at the given path
@@ -42,6 +298,27 @@ impl Archive<'_> {
         .unwrap_or(false)
     }
 
+    /// Check if the given path is a directory, following symlinks
+    ///
+    /// Returns `false` if the path does not exist, or cannot be opened.
+    #[must_use]
+    pub fn is_dir(&self, path: &str) -> bool {
+        self.is_file_type(path, FileType::Directory)
+    }
+
+    /// Check if the given path is a regular file, following symlinks
+    ///
+    /// Returns `false` if the path does not exist, or cannot be opened.
+    #[must_use]
+    pub fn is_file(&self, path: &str) -> bool {
+        self.is_file_type(path, FileType::File)
+    }
+
+    fn is_file_type(&self, path: &str, file_type: FileType) -> bool {
+        self.open(path)
+            .is_ok_and(|file| file.file_type() == Some(file_type))
+    }
+
     pub fn permissions(&self, path: &str) -> error::Result<Permissions> {
         run_with_cstr(path, |path| {
             let mut err = 0;
@@ -55,3 +332,29 @@ impl Archive<'_> {
         })
     }
 }
+
+fn read_capped(file: &File<'_>, max: usize) -> error::Result<Vec<u8>> {
+    let mut reader = file.reader()?;
+    let size = match usize::try_from(file.size()) {
+        Ok(size) => size,
+        Err(_) => return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW)),
+    };
+    if size > max {
+        return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS));
+    }
+
+    let mut dst = Vec::with_capacity(size);
+    loop {
+        let buf = reader.fill_buf_raw()?;
+        if buf.is_empty() {
+            break;
+        }
+        if buf.len() > max - dst.len() {
+            return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS));
+        }
+        dst.extend_from_slice(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
+    Ok(dst)
+}
@@ -1,19 +1,88 @@
 use sqsh_sys as ffi;
 
-use bstr::BStr;
+use bstr::{BStr, BString};
 use std::error::Error as StdError;
 use std::ffi::{c_int, CStr};
 use std::fmt::{Debug, Display, Formatter};
 use std::io;
 
+/// An error from the archive, or from converting input to a form libsqsh accepts.
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Error(pub ffi::SqshError);
+pub enum Error {
+    /// An error reported by libsqsh.
+    Sqsh(ffi::SqshError),
+    /// A path or file contents were not valid UTF-8.
+    InvalidUtf8,
+    /// A path contained an interior NUL byte, which cannot be passed to libsqsh.
+    InteriorNul,
+}
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// An [`Error`] along with the path which was being accessed when it occurred.
+///
+/// Returned by [`Archive::open_with_context`](crate::Archive::open_with_context), so callers can
+/// report which path failed without keeping it around themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathError {
+    path: BString,
+    error: Error,
+}
+
+impl PathError {
+    pub(crate) fn new(path: impl Into<BString>, error: Error) -> Self {
+        Self {
+            path: path.into(),
+            error,
+        }
+    }
+
+    /// The path which was being accessed.
+    #[must_use]
+    pub fn path(&self) -> &BStr {
+        self.path.as_ref()
+    }
+
+    /// The underlying error.
+    #[must_use]
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// Discard the path, returning the underlying error.
+    #[must_use]
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl From<PathError> for Error {
+    fn from(err: PathError) -> Self {
+        err.error
+    }
+}
+
+impl From<PathError> for io::Error {
+    fn from(err: PathError) -> Self {
+        io::Error::new(err.error.io_error_kind(), err)
+    }
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.path, self.error)
+    }
+}
+
+impl StdError for PathError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.error)
+    }
+}
+
 pub(crate) const fn new(err: c_int) -> Error {
     let err = err.unsigned_abs();
-    Error(ffi::SqshError(err))
+    Error::Sqsh(ffi::SqshError(err))
 }
 
 impl Error {
@@ -21,19 +90,110 @@ impl Error {
     // Safety: `f` must not call `with_str` on any Errors, or call sqsh_error_str
     // calls to `sqsh_error_str` will invalidate any previously returned pointer on that thread,
     // so we cannot allow it to be called again while we're looking at the result.
-    unsafe fn with_str<F, O>(&self, f: F) -> O
+    unsafe fn with_str<F, O>(err: ffi::SqshError, f: F) -> O
     where
         // Rust lifetime rules ensure O cannot reference the CStr, which is good
         F: FnOnce(&CStr) -> O,
     {
-        let s = ffi::sqsh_error_str(self.0 .0 as c_int);
+        let s = ffi::sqsh_error_str(err.0 as c_int);
 
         f(CStr::from_ptr(s))
     }
 
+    /// Returns the libsqsh error, if this error was reported by libsqsh.
+    #[must_use]
+    pub fn sqsh_error(&self) -> Option<ffi::SqshError> {
+        match *self {
+            Self::Sqsh(err) => Some(err),
+            Self::InvalidUtf8 | Self::InteriorNul => None,
+        }
+    }
+
+    /// Returns a stable identifier for the kind of error, e.g. `"NoSuchFile"`.
+    ///
+    /// Unlike the message returned by `Display`, this is not expected to change between versions,
+    /// so it is suitable for matching against in tests or logs. Errors from the operating system
+    /// are all named `"Os"`.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        let err = match *self {
+            Self::Sqsh(err) => err,
+            Self::InvalidUtf8 => return "InvalidUtf8",
+            Self::InteriorNul => return "InteriorNul",
+        };
+        if err.0 < ffi::SqshError::SQSH_ERROR_SECTION_START.0 {
+            return "Os";
+        }
+        match err {
+            ffi::SqshError::SQSH_ERROR_SUPERBLOCK_TOO_SMALL => "SuperblockTooSmall",
+            ffi::SqshError::SQSH_ERROR_WRONG_MAGIC => "WrongMagic",
+            ffi::SqshError::SQSH_ERROR_BLOCKSIZE_MISMATCH => "BlocksizeMismatch",
+            ffi::SqshError::SQSH_ERROR_SIZE_MISMATCH => "SizeMismatch",
+            ffi::SqshError::SQSH_ERROR_COMPRESSION_INIT => "CompressionInit",
+            ffi::SqshError::SQSH_ERROR_COMPRESSION_UNSUPPORTED => "CompressionUnsupported",
+            ffi::SqshError::SQSH_ERROR_COMPRESSION_DECOMPRESS => "CompressionDecompress",
+            ffi::SqshError::SQSH_ERROR_UNKNOWN_FILE_TYPE => "UnknownFileType",
+            ffi::SqshError::SQSH_ERROR_NOT_A_DIRECTORY => "NotADirectory",
+            ffi::SqshError::SQSH_ERROR_NOT_A_FILE => "NotAFile",
+            ffi::SqshError::SQSH_ERROR_MALLOC_FAILED => "MallocFailed",
+            ffi::SqshError::SQSH_ERROR_MUTEX_INIT_FAILED => "MutexInitFailed",
+            ffi::SqshError::SQSH_ERROR_MUTEX_LOCK_FAILED => "MutexLockFailed",
+            ffi::SqshError::SQSH_ERROR_MUTEX_DESTROY_FAILED => "MutexDestroyFailed",
+            ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS => "OutOfBounds",
+            ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW => "IntegerOverflow",
+            ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE => "NoSuchFile",
+            ffi::SqshError::SQSH_ERROR_NO_SUCH_XATTR => "NoSuchXattr",
+            ffi::SqshError::SQSH_ERROR_NO_FRAGMENT_TABLE => "NoFragmentTable",
+            ffi::SqshError::SQSH_ERROR_NO_EXTENDED_DIRECTORY => "NoExtendedDirectory",
+            ffi::SqshError::SQSH_ERROR_NO_EXPORT_TABLE => "NoExportTable",
+            ffi::SqshError::SQSH_ERROR_NO_XATTR_TABLE => "NoXattrTable",
+            ffi::SqshError::SQSH_ERROR_NO_COMPRESSION_OPTIONS => "NoCompressionOptions",
+            ffi::SqshError::SQSH_ERROR_MAPPER_INIT => "MapperInit",
+            ffi::SqshError::SQSH_ERROR_MAPPER_MAP => "MapperMap",
+            ffi::SqshError::SQSH_ERROR_CURL_INVALID_RANGE_HEADER => "CurlInvalidRangeHeader",
+            ffi::SqshError::SQSH_ERROR_INVALID_ARGUMENT => "InvalidArgument",
+            ffi::SqshError::SQSH_ERROR_WALKER_CANNOT_GO_UP => "WalkerCannotGoUp",
+            ffi::SqshError::SQSH_ERROR_WALKER_CANNOT_GO_DOWN => "WalkerCannotGoDown",
+            ffi::SqshError::SQSH_ERROR_CORRUPTED_INODE => "CorruptedInode",
+            ffi::SqshError::SQSH_ERROR_CORRUPTED_DIRECTORY_ENTRY => "CorruptedDirectoryEntry",
+            ffi::SqshError::SQSH_ERROR_INTERNAL => "Internal",
+            ffi::SqshError::SQSH_ERROR_INODE_MAP_IS_INCONSISTENT => "InodeMapIsInconsistent",
+            ffi::SqshError::SQSH_ERROR_XATTR_SIZE_MISMATCH => "XattrSizeMismatch",
+            ffi::SqshError::SQSH_ERROR_UNSUPPORTED_VERSION => "UnsupportedVersion",
+            ffi::SqshError::SQSH_ERROR_TOO_MANY_SYMLINKS_FOLLOWED => "TooManySymlinksFollowed",
+            ffi::SqshError::SQSH_ERROR_CORRUPTED_DIRECTORY_HEADER => "CorruptedDirectoryHeader",
+            ffi::SqshError::SQSH_ERROR_COMPRESSION_FINISHED => "CompressionFinished",
+            ffi::SqshError::SQSH_ERROR_NO_SUCH_ELEMENT => "NoSuchElement",
+            ffi::SqshError::SQSH_ERROR_DIRECTORY_RECURSION => "DirectoryRecursion",
+            ffi::SqshError::SQSH_ERROR_INODE_PARENT_MISMATCH => "InodeParentMismatch",
+            ffi::SqshError::SQSH_ERROR_INODE_PARENT_UNSET => "InodeParentUnset",
+            ffi::SqshError::SQSH_ERROR_NOT_A_SYMLINK => "NotASymlink",
+            _ => "Unknown",
+        }
+    }
+
+    /// Returns true if this error was caused by trying to read the contents of a directory.
+    #[must_use]
+    pub fn is_a_directory(&self) -> bool {
+        *self == Self::Sqsh(ffi::SqshError(libc::EISDIR.unsigned_abs()))
+    }
+
+    /// Returns true if this error was caused by trying to list something other than a directory.
+    #[must_use]
+    pub fn is_not_a_directory(&self) -> bool {
+        matches!(
+            *self,
+            Self::Sqsh(ffi::SqshError::SQSH_ERROR_NOT_A_DIRECTORY)
+        ) || *self == Self::Sqsh(ffi::SqshError(libc::ENOTDIR.unsigned_abs()))
+    }
+
     #[must_use]
     pub fn io_error_kind(&self) -> io::ErrorKind {
-        let Self(err) = *self;
+        let err = match *self {
+            Self::Sqsh(err) => err,
+            Self::InvalidUtf8 => return io::ErrorKind::InvalidData,
+            Self::InteriorNul => return io::ErrorKind::InvalidInput,
+        };
         if err.0 < ffi::SqshError::SQSH_ERROR_SECTION_START.0 {
             let io_err = io::Error::from_raw_os_error(err.0 as _);
             return io_err.kind();
@@ -55,13 +215,21 @@ impl Error {
                 io::ErrorKind::NotFound
             }
             ffi::SqshError::SQSH_ERROR_INVALID_ARGUMENT => io::ErrorKind::InvalidInput,
+            // Report the same kind as the OS would for listing a file
+            ffi::SqshError::SQSH_ERROR_NOT_A_DIRECTORY => {
+                io::Error::from_raw_os_error(libc::ENOTDIR).kind()
+            }
+            // Report the same kind as the OS would for a symlink loop
+            ffi::SqshError::SQSH_ERROR_TOO_MANY_SYMLINKS_FOLLOWED => {
+                io::Error::from_raw_os_error(libc::ELOOP).kind()
+            }
             _ => io::ErrorKind::Other,
         }
     }
 
     #[must_use]
     pub fn as_io_error(&self) -> Option<io::Error> {
-        let Self(err) = *self;
+        let err = self.sqsh_error()?;
         if err.0 < ffi::SqshError::SQSH_ERROR_SECTION_START.0 {
             Some(io::Error::from_raw_os_error(err.0 as _))
         } else {
@@ -79,13 +247,29 @@ impl Error {
 
     #[must_use]
     pub(crate) fn to_ffi_result(self) -> c_int {
-        -(self.0 .0 as c_int)
+        let err = match self {
+            Self::Sqsh(err) => err,
+            Self::InvalidUtf8 | Self::InteriorNul => ffi::SqshError::SQSH_ERROR_INVALID_ARGUMENT,
+        };
+        -(err.0 as c_int)
     }
 }
 
 impl From<std::ffi::NulError> for Error {
     fn from(_: std::ffi::NulError) -> Self {
-        Self(ffi::SqshError::SQSH_ERROR_INVALID_ARGUMENT)
+        Self::InteriorNul
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(_: std::str::Utf8Error) -> Self {
+        Self::InvalidUtf8
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        Self::InvalidUtf8
     }
 }
 
@@ -97,25 +281,35 @@ impl From<Error> for io::Error {
 
 impl From<ffi::SqshError> for Error {
     fn from(err: ffi::SqshError) -> Self {
-        Self(err)
+        Self::Sqsh(err)
     }
 }
 
 impl From<std::num::TryFromIntError> for Error {
     fn from(_: std::num::TryFromIntError) -> Self {
-        Self(ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW)
+        Self::Sqsh(ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW)
     }
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        unsafe { self.with_str(|s| Debug::fmt(s, f)) }
+        match *self {
+            Self::Sqsh(err) => unsafe { Self::with_str(err, |s| Debug::fmt(s, f)) },
+            Self::InvalidUtf8 => f.write_str("InvalidUtf8"),
+            Self::InteriorNul => f.write_str("InteriorNul"),
+        }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        unsafe { self.with_str(|s| Display::fmt(BStr::new(s.to_bytes()), f)) }
+        match *self {
+            Self::Sqsh(err) => unsafe {
+                Self::with_str(err, |s| Display::fmt(BStr::new(s.to_bytes()), f))
+            },
+            Self::InvalidUtf8 => f.write_str("Invalid UTF-8"),
+            Self::InteriorNul => f.write_str("Interior NUL byte"),
+        }
     }
 }
 
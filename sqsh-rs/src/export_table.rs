@@ -1,5 +1,6 @@
 use crate::{error, Archive, Inode, InodeRef};
 use sqsh_sys as ffi;
+use std::ops::RangeInclusive;
 use std::ptr;
 
 impl Archive<'_> {
@@ -12,7 +13,8 @@ impl Archive<'_> {
                 None => return Err(error::new(err)),
             }
         };
-        Ok(ExportTable { inner })
+        let inode_count = self.superblock().inode_count();
+        Ok(ExportTable { inner, inode_count })
     }
 }
 
@@ -22,6 +24,7 @@ impl Archive<'_> {
 /// references.
 pub struct ExportTable<'archive> {
     inner: &'archive ffi::SqshExportTable,
+    inode_count: u32,
 }
 
 impl<'archive> ExportTable<'archive> {
@@ -36,4 +39,62 @@ impl<'archive> ExportTable<'archive> {
         }
         Ok(InodeRef(inode_ref))
     }
+
+    /// Returns the number of entries in the export table.
+    ///
+    /// This is the same as the number of inodes in the archive.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inode_count as usize
+    }
+
+    /// Returns `true` if the export table has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inode_count == 0
+    }
+
+    /// Returns an iterator over all entries in the export table.
+    ///
+    /// Inodes which fail to resolve are skipped.
+    #[must_use]
+    pub fn iter(&self) -> ExportTableIter<'_, 'archive> {
+        ExportTableIter {
+            table: self,
+            inodes: 1..=self.inode_count,
+        }
+    }
+}
+
+impl<'table, 'archive> IntoIterator for &'table ExportTable<'archive> {
+    type Item = (Inode, InodeRef);
+    type IntoIter = ExportTableIter<'table, 'archive>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`ExportTable`].
+///
+/// Created by [`ExportTable::iter`].
+pub struct ExportTableIter<'table, 'archive> {
+    table: &'table ExportTable<'archive>,
+    inodes: RangeInclusive<u32>,
+}
+
+impl Iterator for ExportTableIter<'_, '_> {
+    type Item = (Inode, InodeRef);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inodes.by_ref().find_map(|index| {
+            let inode = Inode::new(index).ok()?;
+            let inode_ref = self.table.resolve_inode(inode).ok()?;
+            Some((inode, inode_ref))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inodes.size_hint().1)
+    }
 }
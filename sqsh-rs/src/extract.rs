@@ -0,0 +1,257 @@
+use crate::traverse::State;
+use crate::util::sanitize_path;
+use crate::{Archive, Error, File, FileType};
+use bstr::{BStr, BString, ByteSlice};
+use sqsh_sys as ffi;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+
+/// Progress of an extraction, reported while a file is written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress<'a> {
+    /// The path of the file being written, relative to the source of the extraction.
+    pub path: &'a BStr,
+    /// The number of bytes of the file written so far.
+    pub bytes_done: u64,
+    /// The size of the file.
+    pub bytes_total: u64,
+}
+
+/// An error which occurred while extracting a path from an archive.
+#[derive(Debug)]
+pub struct ExtractError {
+    path: BString,
+    kind: ExtractErrorKind,
+}
+
+/// The cause of an [`ExtractError`].
+#[derive(Debug)]
+pub enum ExtractErrorKind {
+    /// Reading or decoding the entry from the archive failed.
+    Archive(Error),
+    /// Writing the entry to disk failed.
+    Io(io::Error),
+}
+
+impl ExtractError {
+    fn archive(path: &BStr, err: Error) -> Self {
+        Self {
+            path: path.to_owned(),
+            kind: ExtractErrorKind::Archive(err),
+        }
+    }
+
+    fn io(path: &BStr, err: io::Error) -> Self {
+        Self {
+            path: path.to_owned(),
+            kind: ExtractErrorKind::Io(err),
+        }
+    }
+
+    /// The path of the entry which failed, relative to the source of the extraction.
+    #[must_use]
+    pub fn path(&self) -> &BStr {
+        self.path.as_ref()
+    }
+
+    /// Whether the error came from the archive or from writing to disk.
+    #[must_use]
+    pub fn kind(&self) -> &ExtractErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExtractErrorKind::Archive(err) => {
+                write!(f, "failed to read {:?} from archive: {err}", self.path)
+            }
+            ExtractErrorKind::Io(err) => write!(f, "failed to write {:?}: {err}", self.path),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ExtractErrorKind::Archive(err) => Some(err),
+            ExtractErrorKind::Io(err) => Some(err),
+        }
+    }
+}
+
+impl Archive<'_> {
+    /// Extract `src` from the archive to `dest` on disk, reporting progress as files are written.
+    ///
+    /// If `src` is a directory, `dest` is created if needed, and everything under `src` is
+    /// extracted into it. Otherwise, `src` is extracted to the path `dest`. Regular files,
+    /// directories and symlinks are extracted, along with their permissions. Other kinds of
+    /// files (devices, fifos and sockets) are skipped. Existing files are overwritten.
+    ///
+    /// Each file is read from the archive exactly once, and every block is decompressed as it is
+    /// written, so a successful extraction also verifies that every file could be decoded, and
+    /// has the size recorded in its inode: there is no need for a separate verification pass.
+    ///
+    /// `progress` is called after every chunk written to a file.
+    ///
+    /// Entries with names which could escape `dest` (`.`, `..`, or names containing `/`) are
+    /// rejected with a "corrupted directory entry" error. Nothing is written through a symlink
+    /// below `dest`, whether it was extracted earlier or already existed: if a path to be
+    /// written passes through one, extraction fails with an I/O error instead. So, unless `dest`
+    /// is modified concurrently, nothing is written outside of `dest`, though extracted symlinks
+    /// may themselves point outside of it.
+    pub fn extract_to_verified(
+        &self,
+        src: &str,
+        dest: impl AsRef<Path>,
+        mut progress: impl FnMut(Progress<'_>),
+    ) -> Result<(), ExtractError> {
+        let dest = dest.as_ref();
+        let root = self
+            .open(src)
+            .map_err(|e| ExtractError::archive(BStr::new(""), e))?;
+        let mut traversal = root
+            .traversal()
+            .map_err(|e| ExtractError::archive(BStr::new(""), e))?;
+
+        loop {
+            let entry = match traversal.advance() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => return Err(ExtractError::archive(BStr::new(""), e)),
+            };
+            let path = entry.path_buf();
+            let path = path.as_bstr();
+            if entry.depth() > 0 && !is_safe_name(entry.name()) {
+                let err = Error::Sqsh(ffi::SqshError::SQSH_ERROR_CORRUPTED_DIRECTORY_ENTRY);
+                return Err(ExtractError::archive(path, err));
+            }
+            let Some(out_path) = sanitize_path(dest, path) else {
+                let err = Error::Sqsh(ffi::SqshError::SQSH_ERROR_CORRUPTED_DIRECTORY_ENTRY);
+                return Err(ExtractError::archive(path, err));
+            };
+            let file = entry.open().map_err(|e| ExtractError::archive(path, e))?;
+            if entry.state() != State::DirectorySecond {
+                check_no_symlinks(dest, &out_path, path)?;
+            }
+
+            match entry.state() {
+                State::DirectoryFirst => {
+                    if is_symlink(&out_path) {
+                        return Err(ExtractError::io(path, symlink_error()));
+                    }
+                    fs::create_dir_all(&out_path).map_err(|e| ExtractError::io(path, e))?;
+                }
+                // Set permissions after the contents are written, in case it's not writable
+                State::DirectorySecond => set_permissions(&file, &out_path, path)?,
+                State::Normal => match file.file_type() {
+                    Some(FileType::File) => {
+                        write_file(&file, &out_path, path, &mut progress)?;
+                        set_permissions(&file, &out_path, path)?;
+                    }
+                    Some(FileType::Symlink) => {
+                        let target = file.symlink_path().unwrap_or_default();
+                        remove_existing(&out_path, path)?;
+                        std::os::unix::fs::symlink(OsStr::from_bytes(target), &out_path)
+                            .map_err(|e| ExtractError::io(path, e))?;
+                    }
+                    // Directories at the maximum depth, devices, fifos, and sockets
+                    _ => {}
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_safe_name(name: &BStr) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(&b'/')
+}
+
+// Check that none of the existing parents of `out_path` below `dest` are symlinks, which could
+// redirect writes outside of `dest`
+fn check_no_symlinks(dest: &Path, out_path: &Path, path: &BStr) -> Result<(), ExtractError> {
+    let Ok(relative) = out_path.strip_prefix(dest) else {
+        return Ok(());
+    };
+    let mut components = relative.components();
+    // The entry itself is handled by the caller
+    components.next_back();
+    let mut current = dest.to_owned();
+    for component in components {
+        current.push(component);
+        if is_symlink(&current) {
+            return Err(ExtractError::io(path, symlink_error()));
+        }
+    }
+    Ok(())
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+fn symlink_error() -> io::Error {
+    io::Error::from_raw_os_error(libc::ELOOP)
+}
+
+// Remove an existing file at `out_path`, without following it if it's a symlink
+fn remove_existing(out_path: &Path, path: &BStr) -> Result<(), ExtractError> {
+    match fs::remove_file(out_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(ExtractError::io(path, e)),
+        _ => Ok(()),
+    }
+}
+
+fn set_permissions(file: &File<'_>, out_path: &Path, path: &BStr) -> Result<(), ExtractError> {
+    let mode = file.permissions().to_octal();
+    fs::set_permissions(out_path, fs::Permissions::from_mode(mode.into()))
+        .map_err(|e| ExtractError::io(path, e))
+}
+
+fn write_file(
+    file: &File<'_>,
+    out_path: &Path,
+    path: &BStr,
+    progress: &mut impl FnMut(Progress<'_>),
+) -> Result<(), ExtractError> {
+    let bytes_total = file.size();
+    remove_existing(out_path, path)?;
+    let mut out = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(out_path)
+        .map_err(|e| ExtractError::io(path, e))?;
+    let mut reader = file.reader().map_err(|e| ExtractError::archive(path, e))?;
+    let mut bytes_done = 0;
+    loop {
+        let data = reader
+            .fill_buf_raw()
+            .map_err(|e| ExtractError::archive(path, e))?;
+        if data.is_empty() {
+            break;
+        }
+        out.write_all(data).map_err(|e| ExtractError::io(path, e))?;
+        let len = data.len();
+        reader.consume(len);
+        bytes_done += len as u64;
+        progress(Progress {
+            path,
+            bytes_done,
+            bytes_total,
+        });
+    }
+    if bytes_done != bytes_total {
+        let err = Error::Sqsh(ffi::SqshError::SQSH_ERROR_SIZE_MISMATCH);
+        return Err(ExtractError::archive(path, err));
+    }
+    out.flush().map_err(|e| ExtractError::io(path, e))
+}
@@ -1,20 +1,32 @@
 use crate::traverse::Traversal;
 use crate::utils::small_c_string::run_with_cstr;
 use crate::{
-    error, Archive, DirectoryIterator, FileType, Inode, InodeRef, Permissions, Reader,
-    XattrIterator,
+    error, Archive, DirectoryIterator, Error, FileType, GuardedReader, Inode, InodeRef, Lines,
+    Metadata, PathError, Permissions, Reader, XattrIterator,
 };
 use bstr::BStr;
 use sqsh_sys as ffi;
 use std::ffi::CStr;
 use std::fmt;
+use std::hash::Hasher;
+use std::io::{self, BufRead};
+use std::mem::ManuallyDrop;
+use std::ops::Range;
 use std::ptr::NonNull;
+use std::sync::Arc;
+
+// The xattr index of an inode without extended attributes
+const NO_XATTRS: u32 = 0xFFFF_FFFF;
+// The bits of the stored size of a data block which hold the size
+const BLOCK_SIZE_MASK: u32 = 0x00FF_FFFF;
 
 /// Methods for opening files on an archive.
 impl Archive<'_> {
     /// Open a file by path.
     ///
-    /// This will follow symlinks. Use [`Self::open_nofollow`] to avoid following symlinks.
+    /// This will follow symlinks, unless disabled with
+    /// [`ArchiveBuilder::follow_symlinks`](crate::ArchiveBuilder::follow_symlinks). Use
+    /// [`Self::open_nofollow`] to avoid following symlinks.
     pub fn open(&self, path: &str) -> error::Result<File<'_>> {
         run_with_cstr(path, |path| self.open_raw(path))
     }
@@ -24,8 +36,29 @@ impl Archive<'_> {
         run_with_cstr(path, |path| self.open_raw_nofollow(path))
     }
 
+    /// Open a file by path, including the path in any error.
+    ///
+    /// This behaves like [`Self::open`], but on failure the returned [`PathError`] carries the
+    /// requested path, which is included when the error is displayed.
+    pub fn open_with_context(&self, path: &str) -> error::Result<File<'_>, PathError> {
+        self.open(path).map_err(|err| PathError::new(path, err))
+    }
+
+    /// Open a file by path, returning it along with its inode reference.
+    ///
+    /// This will follow symlinks, the inode reference is that of the target. This is convenient
+    /// when registering opened files, e.g. in an [`InodeMap`](crate::InodeMap).
+    pub fn open_with_ref(&self, path: &str) -> error::Result<(File<'_>, InodeRef)> {
+        let file = self.open(path)?;
+        let inode_ref = file.inode_ref();
+        Ok((file, inode_ref))
+    }
+
     /// Open a file using a raw path (a CStr)
     pub fn open_raw(&self, path: &CStr) -> error::Result<File<'_>> {
+        if !self.inner.follow_symlinks {
+            return self.open_raw_nofollow(path);
+        }
         let mut err = 0;
         let file = unsafe { ffi::sqsh_open(self.inner.as_ptr(), path.as_ptr(), &mut err) };
         let file = match NonNull::new(file) {
@@ -49,6 +82,8 @@ impl Archive<'_> {
     }
 
     /// Open a file by inode reference.
+    ///
+    /// Symlinks are never followed: the file is the inode referred to by `inode_ref`.
     pub fn open_ref(&self, inode_ref: InodeRef) -> error::Result<File<'_>> {
         let mut err = 0;
         let file = unsafe { ffi::sqsh_open_by_ref(self.inner.as_ptr(), inode_ref.0, &mut err) };
@@ -58,6 +93,72 @@ impl Archive<'_> {
         };
         Ok(unsafe { File::new(file) })
     }
+
+    /// List the directory at the given path.
+    ///
+    /// This is a shortcut for `archive.open(path)?.into_dir()`, and will follow symlinks.
+    /// If the path does not refer to a directory, this will return a "not a directory" error.
+    pub fn read_dir(&self, path: &str) -> error::Result<DirectoryIterator<'_, '_>> {
+        self.open(path)?.into_dir()
+    }
+
+    /// List the directory with the given inode reference.
+    ///
+    /// This is a shortcut for `archive.open_ref(inode_ref)?.into_dir()`. Like
+    /// [`Self::open_ref`], symlinks are never followed.
+    pub fn read_dir_ref(&self, inode_ref: InodeRef) -> error::Result<DirectoryIterator<'_, '_>> {
+        self.open_ref(inode_ref)?.into_dir()
+    }
+
+    /// Look up a direct child of the directory with the given inode reference.
+    ///
+    /// Returns the inode reference and metadata of the child, or `None` if the directory has no
+    /// entry named `name`. This maps directly onto the `lookup` operation of a FUSE filesystem.
+    pub fn lookup(
+        &self,
+        parent_ref: InodeRef,
+        name: &[u8],
+    ) -> error::Result<Option<(InodeRef, Metadata)>> {
+        let mut dir = self.read_dir_ref(parent_ref)?;
+        let Some(entry) = dir.advance_lookup(name)? else {
+            return Ok(None);
+        };
+        let file = entry.open()?;
+        Ok(Some((file.inode_ref(), file.metadata())))
+    }
+
+    /// List the directory with the given inode reference, skipping the first `offset` entries.
+    ///
+    /// This maps onto the `readdir` operation of a FUSE filesystem, which may be called
+    /// repeatedly to continue listing a directory: the offset of an entry is its index in the
+    /// directory plus one, so listing can resume after the last entry returned.
+    pub fn readdir(
+        &self,
+        dir_ref: InodeRef,
+        offset: u64,
+    ) -> error::Result<DirectoryIterator<'_, '_>> {
+        let mut dir = self.read_dir_ref(dir_ref)?;
+        for _ in 0..offset {
+            if dir.advance()?.is_none() {
+                break;
+            }
+        }
+        Ok(dir)
+    }
+}
+
+impl<'a> Archive<'a> {
+    /// Open a file by path like [`Self::open`], but the file holds a clone of the archive
+    /// instead of borrowing it.
+    pub(crate) fn open_owned(&self, path: &str) -> error::Result<File<'a>> {
+        // Not dropped: ownership of the handle moves to the new file
+        let file = ManuallyDrop::new(self.open(path)?);
+        Ok(File {
+            inner: file.inner,
+            _archive: Some(self.clone()),
+            _marker: std::marker::PhantomData,
+        })
+    }
 }
 
 /// A file in a squashfs archive.
@@ -66,7 +167,10 @@ impl Archive<'_> {
 ///
 /// Files are created from an `Archive` with e.g. [`Archive::open`].
 pub struct File<'archive> {
-    inner: NonNull<ffi::SqshFile>,
+    pub(crate) inner: NonNull<ffi::SqshFile>,
+    // Keeps the archive alive, for files which don't borrow it (see `Archive::open_owned`).
+    // Dropped after `inner` is closed.
+    _archive: Option<Archive<'archive>>,
     _marker: std::marker::PhantomData<&'archive Archive<'archive>>,
 }
 
@@ -74,6 +178,7 @@ impl<'archive> File<'archive> {
     pub(crate) unsafe fn new(inner: NonNull<ffi::SqshFile>) -> Self {
         Self {
             inner,
+            _archive: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -102,6 +207,11 @@ impl<'archive> File<'archive> {
     }
 
     /// Returns whether the file is an extended structure
+    ///
+    /// Extended inodes are used when a file needs fields which the basic inode of its type
+    /// cannot store. Only extended inodes carry an xattr index, so [`Self::xattr_id`] is always
+    /// `None` for basic inodes. Extended regular files also store a sparse byte count and support
+    /// 64-bit sizes, but libsqsh does not expose the sparse count.
     #[must_use]
     pub fn is_extended(&self) -> bool {
         unsafe { ffi::sqsh_file_is_extended(self.inner.as_ptr()) }
@@ -119,6 +229,53 @@ impl<'archive> File<'archive> {
         unsafe { ffi::sqsh_file_size(self.inner.as_ptr()) }
     }
 
+    /// Returns the number of full data blocks of the file.
+    ///
+    /// The tail of the file which doesn't fill a whole block may be stored in a fragment
+    /// instead (see [`Self::uses_fragment`]), in which case it is not counted. This is 0 for
+    /// anything other than a regular file.
+    #[must_use]
+    pub fn block_count(&self) -> u64 {
+        self.raw_block_count().into()
+    }
+
+    // libsqsh returns u32::MAX for anything other than a regular file
+    pub(crate) fn raw_block_count(&self) -> u32 {
+        if self.file_type() != Some(FileType::File) {
+            return 0;
+        }
+        unsafe { ffi::sqsh_file_block_count(self.inner.as_ptr()) }
+    }
+
+    /// Returns the range of bytes in the archive occupied by the full data blocks of the file.
+    ///
+    /// Offsets are relative to the start of the archive (the superblock). The data blocks of a
+    /// file are stored contiguously, the tail of the file stored in a fragment (see
+    /// [`Self::uses_fragment`]) is not included. Returns `None` if the file has no data blocks.
+    #[must_use]
+    pub fn data_range(&self) -> Option<Range<u64>> {
+        let block_count = self.raw_block_count();
+        if block_count == 0 {
+            return None;
+        }
+        let start = unsafe { ffi::sqsh_file_blocks_start(self.inner.as_ptr()) };
+        let len: u64 = (0..block_count).map(|i| self.stored_block_size(i)).sum();
+        Some(start..start + len)
+    }
+
+    // The size of a data block in the archive, 0 for sparse blocks
+    pub(crate) fn stored_block_size(&self, index: u32) -> u64 {
+        let size = unsafe { ffi::sqsh_file_block_size(self.inner.as_ptr(), index) };
+        // The upper bits of the on-disk size are flags
+        u64::from(size & BLOCK_SIZE_MASK)
+    }
+
+    /// Returns true if the tail end of the file is stored in a fragment block.
+    #[must_use]
+    pub fn uses_fragment(&self) -> bool {
+        unsafe { ffi::sqsh_file_has_fragment(self.inner.as_ptr()) }
+    }
+
     /// Getter for the inode number.
     #[must_use]
     pub fn inode(&self) -> Inode {
@@ -149,7 +306,9 @@ impl<'archive> File<'archive> {
     /// After calling this function, the file is (in-place) changed to the target of the symlink(s).
     ///
     /// This function performs recursion detection and will return an error if a
-    /// symlink loop is detected.
+    /// symlink loop is detected, or if more symlinks than the archive's maximum symlink depth
+    /// (see [`ArchiveBuilder::max_symlink_depth`](crate::ArchiveBuilder::max_symlink_depth))
+    /// would need to be followed.
     pub fn follow_all_symlinks(&mut self) -> error::Result<()> {
         let err = unsafe { ffi::sqsh_file_symlink_resolve_all(self.inner.as_ptr()) };
         if err != 0 {
@@ -200,22 +359,99 @@ impl<'archive> File<'archive> {
     }
 
     /// Returns index of the extended attribute inside of the xattr table.
+    ///
+    /// Returns `None` if the file has no extended attributes. This is always the case for basic
+    /// inodes (see [`Self::is_extended`]), which cannot store an xattr index.
     #[must_use]
-    pub fn xattr_id(&self) -> u32 {
-        unsafe { ffi::sqsh_file_xattr_index(self.inner.as_ptr()) }
+    pub fn xattr_id(&self) -> Option<u32> {
+        let index = unsafe { ffi::sqsh_file_xattr_index(self.inner.as_ptr()) };
+        (index != NO_XATTRS).then_some(index)
+    }
+
+    /// Returns true if the file has any extended attributes.
+    ///
+    /// This only checks the inode, it does not need to read the xattr table.
+    #[must_use]
+    pub fn has_xattrs(&self) -> bool {
+        self.xattr_id().is_some()
+    }
+
+    /// Returns the number of extended attributes of the file.
+    ///
+    /// Returns 0 without reading the xattr table if the file has no extended attributes.
+    pub fn xattr_count(&self) -> error::Result<usize> {
+        if !self.has_xattrs() {
+            return Ok(0);
+        }
+        let mut xattrs = self.xattrs()?;
+        let mut count = 0;
+        while xattrs.advance()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Returns a snapshot of the metadata of the file.
+    #[must_use]
+    pub fn metadata(&self) -> Metadata {
+        Metadata::new(self)
     }
 
     /// Returns an iterator over the directory entries of the file.
     ///
-    /// If the file is not a directory, this will return an error.
+    /// If the file is not a directory, this will return a "not a directory" error.
     pub fn as_dir(&self) -> error::Result<DirectoryIterator<'_, 'archive>> {
+        let dir_iter = self.dir_iter_raw()?;
+        Ok(unsafe { DirectoryIterator::new(dir_iter) })
+    }
+
+    /// Converts the file into an iterator over its directory entries.
+    ///
+    /// Unlike [`Self::as_dir`], the returned iterator owns the file, so it only borrows from the
+    /// archive.
+    ///
+    /// If the file is not a directory, this will return a "not a directory" error.
+    pub fn into_dir(self) -> error::Result<DirectoryIterator<'archive, 'archive>> {
+        self.try_into_dir().map_err(|(err, _)| err)
+    }
+
+    /// Converts the file into an iterator over its directory entries, or returns the file back.
+    ///
+    /// Like [`Self::into_dir`], but on failure (e.g. if the file is not a directory), the file is
+    /// returned along with the error, so it can still be used.
+    pub fn try_into_dir(self) -> Result<DirectoryIterator<'archive, 'archive>, (Error, Self)> {
+        match self.dir_iter_raw() {
+            Ok(dir_iter) => Ok(unsafe { DirectoryIterator::new_owned(dir_iter, self) }),
+            Err(err) => Err((err, self)),
+        }
+    }
+
+    fn dir_iter_raw(&self) -> error::Result<NonNull<ffi::SqshDirectoryIterator>> {
+        // Check up front, for a clearer error than libsqsh would give
+        if self.file_type() != Some(FileType::Directory) {
+            return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_NOT_A_DIRECTORY));
+        }
         let mut err = 0;
         let dir_iter = unsafe { ffi::sqsh_directory_iterator_new(self.inner.as_ptr(), &mut err) };
-        let dir_iter = match NonNull::new(dir_iter) {
-            Some(dir_iter) => dir_iter,
-            None => return Err(error::new(err)),
-        };
-        Ok(unsafe { DirectoryIterator::new(dir_iter) })
+        match NonNull::new(dir_iter) {
+            Some(dir_iter) => Ok(dir_iter),
+            None => Err(error::new(err)),
+        }
+    }
+
+    /// Open a direct child of this directory by name.
+    ///
+    /// This avoids resolving a full path from the root of the archive when a handle to the
+    /// parent directory is already available.
+    ///
+    /// If the file is not a directory, this will return an error. If no entry with the given
+    /// name exists, this will return a "no such file" error.
+    pub fn open_child(&self, name: &[u8]) -> error::Result<File<'archive>> {
+        let mut dir = self.as_dir()?;
+        match dir.advance_lookup(name)? {
+            Some(entry) => entry.open(),
+            None => Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE)),
+        }
     }
 
     /// Returns an iterator over the extended attributes of the file.
@@ -230,27 +466,184 @@ impl<'archive> File<'archive> {
     }
 
     /// Returns a new reader for the file.
+    ///
+    /// Fails with an "is a directory" error (see [`Error::is_a_directory`]) if the file is a
+    /// directory.
+    ///
+    /// Decompressed blocks are cached in the archive-wide compression LRU, which is shared with
+    /// every other reader of the archive. Large sequential reads will therefore evict blocks
+    /// cached for other readers: libsqsh has no way to bypass the cache for a single reader,
+    /// only to disable it for the whole archive (with a `compression_lru_size` of -1).
+    // TODO: a streaming reader which doesn't populate the shared cache, if libsqsh ever
+    //       supports it
+    // TODO: try_as_slice, borrowing the contents of uncompressed, unfragmented files directly
+    //       from the archive mapping. libsqsh only exposes mapped data through iterators, which
+    //       unmap it again when they're freed, so there is nothing to borrow from the `File`.
+    //       Once that's possible, add `read_cow` returning `Cow::Borrowed` for such files, and
+    //       falling back to `read_into` otherwise.
     pub fn reader(&self) -> error::Result<Reader<'_>> {
+        let iterator = self.iterator_raw()?;
+        Ok(unsafe { Reader::new(self, iterator) })
+    }
+
+    /// Returns a new reader for the file, which fails if the file is decompressed to more than
+    /// `max_ratio` times its compressed size.
+    ///
+    /// This guards against decompression bombs when reading untrusted archives. The ratio is
+    /// checked before each block is read, from the block sizes stored in the inode, so an
+    /// offending block is never decompressed: reading fails with an "out of bounds" error
+    /// instead.
+    ///
+    /// Each block is counted as its compressed size, plus the 4 bytes of the inode used to
+    /// store that size. This means sparse blocks, which take no space in the archive, count as
+    /// 4 bytes each, and large sparse files will likely exceed the ratio. The tail of the file
+    /// stored in a fragment is counted as uncompressed, since libsqsh doesn't expose the size of
+    /// fragment blocks.
+    pub fn reader_guarded(&self, max_ratio: u32) -> error::Result<GuardedReader<'_>> {
+        Ok(GuardedReader::new(self.reader()?, max_ratio))
+    }
+
+    /// Returns a new reader for the file, taking ownership of the file.
+    ///
+    /// Unlike [`Self::reader`], the reader does not borrow from the file, so it can outlive it.
+    pub fn into_reader(self) -> error::Result<Reader<'archive>> {
+        Reader::new_shared(Arc::new(self))
+    }
+
+    pub(crate) fn iterator_raw(&self) -> error::Result<NonNull<ffi::SqshFileIterator>> {
+        // libsqsh reports a generic "not a file" error for directories
+        if self.file_type() == Some(FileType::Directory) {
+            return Err(error::new(libc::EISDIR));
+        }
         let mut err = 0;
         let iterator = unsafe { ffi::sqsh_file_iterator_new(self.inner.as_ptr(), &mut err) };
-        let iterator = match NonNull::new(iterator) {
-            Some(iterator) => iterator,
-            None => return Err(error::new(err)),
+        match NonNull::new(iterator) {
+            Some(iterator) => Ok(iterator),
+            None => Err(error::new(err)),
+        }
+    }
+
+    /// Returns an iterator over the lines of the file.
+    ///
+    /// Lines are split on `\n`, and do not include the trailing `\n` (or `\r\n`). Lines are
+    /// returned as bytes, the file does not need to be valid UTF-8.
+    pub fn lines(&self) -> error::Result<Lines<'_>> {
+        Ok(Lines::new(self.reader()?))
+    }
+
+    /// Read the entire contents of the file into `buf`.
+    ///
+    /// `buf` is cleared, and enough space is reserved for the whole file up front, so a buffer
+    /// can be reused across many files without reallocating. Returns the number of bytes read.
+    pub fn read_into(&self, buf: &mut Vec<u8>) -> error::Result<usize> {
+        let size = match usize::try_from(self.size()) {
+            Ok(size) => size,
+            Err(_) => return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_INTEGER_OVERFLOW)),
         };
-        Ok(unsafe { Reader::new(iterator) })
+        let mut reader = self.reader()?;
+
+        buf.clear();
+        buf.reserve(size);
+        loop {
+            let data = reader.fill_buf_raw()?;
+            if data.is_empty() {
+                break;
+            }
+            buf.extend_from_slice(data);
+            let len = data.len();
+            reader.consume(len);
+        }
+        Ok(buf.len())
+    }
+
+    /// Read from the file at `offset`, filling as much of `buf` as possible.
+    ///
+    /// Returns the number of bytes read, which is only less than `buf.len()` if the end of the
+    /// file was reached. Reading at or past the end of the file returns 0.
+    ///
+    /// This is the equivalent of [`std::os::unix::fs::FileExt::read_at`]: each call creates a
+    /// new reader, so for sequential reads, [`File::reader`] is more efficient.
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> error::Result<usize> {
+        if offset >= self.size() || buf.is_empty() {
+            return Ok(0);
+        }
+        let mut reader = self.reader()?;
+        reader.skip(offset)?;
+
+        let mut read = 0;
+        while read < buf.len() {
+            let data = reader.fill_buf_raw()?;
+            if data.is_empty() {
+                break;
+            }
+            let len = data.len().min(buf.len() - read);
+            buf[read..read + len].copy_from_slice(&data[..len]);
+            reader.consume(len);
+            read += len;
+        }
+        Ok(read)
+    }
+
+    /// Read up to the first `n` bytes of the file.
+    ///
+    /// Only the blocks needed to cover the first `n` bytes are decompressed, which makes this
+    /// suitable for sniffing magic numbers without reading the whole file. The returned buffer is
+    /// shorter than `n` if the file is.
+    pub fn read_head(&self, n: usize) -> error::Result<Vec<u8>> {
+        let len = usize::try_from(self.size()).map_or(n, |size| size.min(n));
+        let mut buf = vec![0; len];
+        let read = self.read_at(0, &mut buf)?;
+        buf.truncate(read);
+        Ok(buf)
+    }
+
+    /// Feed the contents of the file into `hasher`.
+    ///
+    /// The file is streamed through the hasher one block at a time, without buffering the whole
+    /// file. The contents are passed to [`Hasher::write`] in chunks whose sizes depend on the
+    /// layout of the archive, so `hasher` should produce the same result regardless of how its
+    /// input is split (as is the case for e.g. std's `DefaultHasher`), in order to get the same
+    /// hash for the same contents in different archives.
+    pub fn hash<H: Hasher>(&self, hasher: &mut H) -> error::Result<()> {
+        let mut reader = self.reader()?;
+        loop {
+            let data = reader.fill_buf_raw()?;
+            if data.is_empty() {
+                break;
+            }
+            hasher.write(data);
+            let len = data.len();
+            reader.consume(len);
+        }
+        Ok(())
     }
 
     /// Returns a new traversal for the file.
     ///
     /// A traversal is used to recursively traverse the file tree starting from this file/directory.
-    pub fn traversal(&self) -> error::Result<Traversal> {
+    pub fn traversal(&self) -> error::Result<Traversal<'_>> {
+        let traversal = self.traversal_raw()?;
+        Ok(unsafe { Traversal::new(traversal, self.inode_ref(), self.inode()) })
+    }
+
+    /// Converts the file into a traversal starting from it.
+    ///
+    /// Unlike [`Self::traversal`], the returned traversal owns the file, so it only borrows
+    /// from the archive.
+    pub fn into_traversal(self) -> error::Result<Traversal<'archive>> {
+        let traversal = self.traversal_raw()?;
+        let mut traversal = unsafe { Traversal::new(traversal, self.inode_ref(), self.inode()) };
+        traversal.keep_root_file(self);
+        Ok(traversal)
+    }
+
+    fn traversal_raw(&self) -> error::Result<NonNull<ffi::SqshTreeTraversal>> {
         let mut err = 0;
         let traversal = unsafe { ffi::sqsh_tree_traversal_new(self.inner.as_ptr(), &mut err) };
-        let traversal = match NonNull::new(traversal) {
-            Some(traversal) => traversal,
-            None => return Err(error::new(err)),
-        };
-        Ok(unsafe { Traversal::new(traversal) })
+        match NonNull::new(traversal) {
+            Some(traversal) => Ok(traversal),
+            None => Err(error::new(err)),
+        }
     }
 }
 
@@ -283,3 +676,47 @@ impl<'archive> Drop for File<'archive> {
 
 unsafe impl<'archive> Send for File<'archive> {}
 unsafe impl<'archive> Sync for File<'archive> {}
+
+/// A common interface for files, so code can be generic over files from a squashfs archive and
+/// files from other sources.
+///
+/// This trait is object safe, so it can be used as `dyn ArchiveFile`.
+pub trait ArchiveFile {
+    /// Returns the size of the file in bytes.
+    fn size(&self) -> u64;
+
+    /// Returns the type of the file, if known.
+    fn file_type(&self) -> Option<FileType>;
+
+    /// Returns the permissions of the file.
+    fn permissions(&self) -> Permissions;
+
+    /// Returns the modification time, in seconds since the Unix epoch.
+    fn modified_time(&self) -> u32;
+
+    /// Returns a reader over the contents of the file.
+    fn reader(&self) -> io::Result<Box<dyn io::Read + '_>>;
+}
+
+impl ArchiveFile for File<'_> {
+    fn size(&self) -> u64 {
+        File::size(self)
+    }
+
+    fn file_type(&self) -> Option<FileType> {
+        File::file_type(self)
+    }
+
+    fn permissions(&self) -> Permissions {
+        File::permissions(self)
+    }
+
+    fn modified_time(&self) -> u32 {
+        File::modified_time(self)
+    }
+
+    fn reader(&self) -> io::Result<Box<dyn io::Read + '_>> {
+        let reader = File::reader(self)?;
+        Ok(Box::new(reader))
+    }
+}
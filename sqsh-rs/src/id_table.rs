@@ -1,5 +1,6 @@
 use crate::{error, Archive};
 use sqsh_sys as ffi;
+use std::ops::Range;
 use std::ptr;
 
 impl Archive<'_> {
@@ -12,12 +13,18 @@ impl Archive<'_> {
                 None => return Err(error::new(err)),
             }
         };
-        Ok(IdTable { inner })
+        let id_count = self.superblock().id_count();
+        Ok(IdTable { inner, id_count })
     }
 }
 
+/// The table of uids and gids used in the archive.
+///
+/// Inodes store indexes into this table rather than ids. Every id used by any file in the
+/// archive, as either a uid or a gid, is stored in this table once.
 pub struct IdTable<'archive> {
     inner: &'archive ffi::SqshIdTable,
+    id_count: u16,
 }
 
 impl<'archive> IdTable<'archive> {
@@ -31,4 +38,59 @@ impl<'archive> IdTable<'archive> {
             None
         }
     }
+
+    /// Returns the number of ids in the table.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        usize::from(self.id_count)
+    }
+
+    /// Returns `true` if the table has no ids.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.id_count == 0
+    }
+
+    /// Returns an iterator over all ids in the table, in table order.
+    ///
+    /// This is every uid and gid used in the archive, without needing to visit any inodes.
+    /// Entries which fail to read are skipped.
+    #[must_use]
+    pub fn iter(&self) -> IdTableIter<'_, 'archive> {
+        IdTableIter {
+            table: self,
+            indexes: 0..self.len(),
+        }
+    }
+}
+
+impl<'table, 'archive> IntoIterator for &'table IdTable<'archive> {
+    type Item = u32;
+    type IntoIter = IdTableIter<'table, 'archive>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the ids in an [`IdTable`].
+///
+/// Created by [`IdTable::iter`].
+pub struct IdTableIter<'table, 'archive> {
+    table: &'table IdTable<'archive>,
+    indexes: Range<usize>,
+}
+
+impl Iterator for IdTableIter<'_, '_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indexes
+            .by_ref()
+            .find_map(|index| self.table.get(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.indexes.size_hint().1)
+    }
 }
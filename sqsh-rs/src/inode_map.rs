@@ -1,4 +1,4 @@
-use crate::{error, Archive, Inode, InodeRef};
+use crate::{error, Archive, Error, Inode, InodeRef};
 use sqsh_sys as ffi;
 use std::ptr;
 
@@ -14,6 +14,45 @@ impl Archive<'_> {
         };
         Ok(InodeMap { inner })
     }
+
+    /// Resolve an inode number to an inode reference.
+    ///
+    /// This uses the export table if the archive has one. Otherwise, the inode map is checked,
+    /// and if the inode has not been visited yet, the whole tree is walked until the inode is
+    /// found. Every inode seen during the walk is recorded in the inode map, so later lookups
+    /// can avoid walking the tree again.
+    ///
+    /// Returns a "no such element" error if no inode with the given number exists.
+    pub fn resolve_inode(&self, inode: Inode) -> error::Result<InodeRef> {
+        if self.superblock().has_export_table() {
+            return self.export_table()?.resolve_inode(inode);
+        }
+
+        let inode_map = self.inode_map()?;
+        if let Ok(inode_ref) = inode_map.get(inode) {
+            return Ok(inode_ref);
+        }
+
+        let root = self.root()?;
+        inode_map.set(root.inode(), root.inode_ref())?;
+        if root.inode() == inode {
+            return Ok(root.inode_ref());
+        }
+        let mut traversal = root.traversal()?;
+        while let Some(entry) = traversal.advance()? {
+            if entry.state().is_second_visit() {
+                continue;
+            }
+            let Some(dir_entry) = entry.directory_entry() else {
+                continue;
+            };
+            inode_map.set(dir_entry.inode(), dir_entry.inode_ref())?;
+            if dir_entry.inode() == inode {
+                return Ok(dir_entry.inode_ref());
+            }
+        }
+        Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_NO_SUCH_ELEMENT))
+    }
 }
 
 /// A map of inodes to inode references.
@@ -39,5 +78,17 @@ impl<'archive> InodeMap<'archive> {
         Ok(InodeRef(inode_ref))
     }
 
-    // TODO: sqsh_inode_map_set
+    /// Records the inode reference for a given inode number.
+    ///
+    /// Returns an error if a different inode reference is already known for the inode number.
+    pub fn set(&self, inode_number: Inode, inode_ref: InodeRef) -> error::Result<()> {
+        // Safety: the inode map is internally synchronized, libsqsh only needs a mutable
+        // pointer because the map is modified.
+        let inner = (self.inner as *const ffi::SqshInodeMap).cast_mut();
+        let err = unsafe { ffi::sqsh_inode_map_set2(inner, inode_number.0.get(), inode_ref.0) };
+        if err != 0 {
+            return Err(error::new(err));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,91 @@
+use crate::traverse::State;
+use crate::{error, Archive, FileType};
+use bstr::BStr;
+use serde::Serialize;
+
+impl Archive<'_> {
+    /// Dump the tree of entries under `path` (including `path` itself) as JSON.
+    ///
+    /// Each entry is an object with a `"name"`, `"type"` (e.g. `"file"` or `"directory"`),
+    /// `"size"`, and `"permissions"` (as shown by `ls -l`, e.g. `"rwxr-xr-x"`). Directories also
+    /// have a `"children"` array of their entries. The name of the entry for `path` itself is the
+    /// last component of `path`, which is empty for the root.
+    ///
+    /// Names are arbitrary bytes, and JSON strings must be valid unicode. Names which are valid
+    /// UTF-8 are emitted as a `"name"` string, any other name is emitted losslessly as a
+    /// `"name_bytes"` array of byte values instead.
+    ///
+    /// Symlinks are not followed, except for `path` itself.
+    ///
+    /// Requires the `serde` feature.
+    pub fn tree_json(&self, path: &str) -> error::Result<String> {
+        let file = self.open(path)?;
+        let mut traversal = file.traversal()?;
+        let root_name = path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("");
+        // Directories which have been entered but not yet exited, innermost last
+        let mut open_dirs: Vec<Node> = Vec::new();
+        let mut root = None;
+
+        while let Some(entry) = traversal.advance()? {
+            let node = if entry.state() == State::DirectorySecond {
+                open_dirs.pop().expect("directory exits match entries")
+            } else {
+                let metadata = entry.open()?.metadata();
+                let name = if entry.depth() == 0 {
+                    BStr::new(root_name)
+                } else {
+                    entry.name()
+                };
+                let (name, name_bytes) = match std::str::from_utf8(name) {
+                    Ok(name) => (Some(name.to_owned()), None),
+                    Err(_) => (None, Some(name.to_vec())),
+                };
+                let node = Node {
+                    name,
+                    name_bytes,
+                    file_type: metadata.file_type().map_or("unknown", type_name),
+                    size: metadata.size(),
+                    permissions: metadata.permissions().to_string(),
+                    children: (entry.state() == State::DirectoryFirst).then(Vec::new),
+                };
+                if node.children.is_some() {
+                    open_dirs.push(node);
+                    continue;
+                }
+                node
+            };
+            match open_dirs.last_mut() {
+                Some(parent) => parent.children.get_or_insert_with(Vec::new).push(node),
+                None => root = Some(node),
+            }
+        }
+        let root = root.expect("a traversal always visits its root");
+        Ok(serde_json::to_string(&root).expect("a tree is always serializable"))
+    }
+}
+
+#[derive(Serialize)]
+struct Node {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name_bytes: Option<Vec<u8>>,
+    #[serde(rename = "type")]
+    file_type: &'static str,
+    size: u64,
+    permissions: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<Node>>,
+}
+
+fn type_name(file_type: FileType) -> &'static str {
+    match file_type {
+        FileType::Directory => "directory",
+        FileType::File => "file",
+        FileType::Symlink => "symlink",
+        FileType::BlockDevice => "block_device",
+        FileType::CharacterDevice => "character_device",
+        FileType::Socket => "socket",
+        FileType::Fifo => "fifo",
+    }
+}
@@ -1,34 +1,47 @@
 #![doc = include_str!("../README.md")]
 
 mod archive;
+mod buffer_pool;
 mod directory;
 mod easy;
 mod error;
 mod export_table;
+#[cfg(unix)]
+mod extract;
 mod file;
 mod id_table;
 mod inode;
 mod inode_map;
+#[cfg(feature = "serde")]
+mod json;
+mod metadata;
 mod path_resolver;
 mod reader;
+mod shared;
 mod source;
 pub mod superblock;
 pub mod traverse;
+pub mod util;
 mod utils;
 mod xattr;
 
-pub use crate::archive::Archive;
-pub use crate::directory::{DirectoryEntry, DirectoryIterator};
-pub use crate::error::{Error, Result};
-pub use crate::export_table::ExportTable;
-pub use crate::file::File;
-pub use crate::id_table::IdTable;
+pub use crate::archive::{Archive, ArchiveBuilder};
+pub use crate::buffer_pool::{BufferPool, PooledBuffer};
+pub use crate::directory::{DirEntryInfo, DirectoryEntry, DirectoryIterator, OwnedDirectoryEntry};
+pub use crate::error::{Error, PathError, Result};
+pub use crate::export_table::{ExportTable, ExportTableIter};
+#[cfg(unix)]
+pub use crate::extract::{ExtractError, ExtractErrorKind, Progress};
+pub use crate::file::{ArchiveFile, File};
+pub use crate::id_table::{IdTable, IdTableIter};
 pub use crate::inode::{Inode, InodeRef, ZeroInode};
 pub use crate::inode_map::InodeMap;
+pub use crate::metadata::{IdNames, IdResolver, Metadata};
 pub use crate::path_resolver::PathResolver;
-pub use crate::reader::Reader;
+pub use crate::reader::{ConcatReader, GuardedReader, Lines, Reader, TakeReader};
+pub use crate::shared::{OwnedReader, SharedArchive};
 pub use crate::source::Source;
-pub use crate::superblock::{Compression, Superblock};
+pub use crate::superblock::{ArchiveInfo, Compression, CompressionSpec, Superblock};
 pub use crate::xattr::{UnknownXattrType, XattrEntry, XattrIterator, XattrType};
 use std::fmt;
 
@@ -65,6 +78,58 @@ impl TryFrom<ffi::SqshFileType> for FileType {
     }
 }
 
+impl FileType {
+    /// Get the file type from the `S_IFMT` bits of a unix mode.
+    ///
+    /// Any permission bits in `mode` are ignored. Returns `None` if the file type bits do not
+    /// match a known file type.
+    #[must_use]
+    // `mode_t` is not a u32 on all platforms
+    #[allow(clippy::unnecessary_cast)]
+    pub const fn from_mode(mode: u32) -> Option<Self> {
+        Some(match mode & libc::S_IFMT as u32 {
+            m if m == libc::S_IFDIR as u32 => FileType::Directory,
+            m if m == libc::S_IFREG as u32 => FileType::File,
+            m if m == libc::S_IFLNK as u32 => FileType::Symlink,
+            m if m == libc::S_IFBLK as u32 => FileType::BlockDevice,
+            m if m == libc::S_IFCHR as u32 => FileType::CharacterDevice,
+            m if m == libc::S_IFSOCK as u32 => FileType::Socket,
+            m if m == libc::S_IFIFO as u32 => FileType::Fifo,
+            _ => return None,
+        })
+    }
+
+    /// Get the `S_IFMT` bits of a unix mode for this file type.
+    #[must_use]
+    // `mode_t` is not a u32 on all platforms
+    #[allow(clippy::unnecessary_cast)]
+    pub const fn to_mode(self) -> u32 {
+        (match self {
+            FileType::Directory => libc::S_IFDIR,
+            FileType::File => libc::S_IFREG,
+            FileType::Symlink => libc::S_IFLNK,
+            FileType::BlockDevice => libc::S_IFBLK,
+            FileType::CharacterDevice => libc::S_IFCHR,
+            FileType::Socket => libc::S_IFSOCK,
+            FileType::Fifo => libc::S_IFIFO,
+        }) as u32
+    }
+
+    /// The character used by `ls -l` to indicate this file type.
+    #[must_use]
+    pub const fn type_char(self) -> char {
+        match self {
+            FileType::Directory => 'd',
+            FileType::File => '-',
+            FileType::Symlink => 'l',
+            FileType::BlockDevice => 'b',
+            FileType::CharacterDevice => 'c',
+            FileType::Fifo => 'p',
+            FileType::Socket => 's',
+        }
+    }
+}
+
 bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub struct Permissions: u16 {
@@ -132,7 +197,62 @@ impl fmt::Display for Permissions {
     }
 }
 
+/// An error which can be returned when parsing [`Permissions`] from an octal string.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParsePermissionsError;
+
+impl fmt::Display for ParsePermissionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid octal permissions")
+    }
+}
+
+impl std::error::Error for ParsePermissionsError {}
+
 impl Permissions {
+    /// Create permissions from a unix mode, ignoring any file type bits.
+    #[must_use]
+    pub const fn from_mode(mode: u16) -> Self {
+        Self::from_bits_retain(mode & 0o7777)
+    }
+
+    /// Parse permissions from an octal string, like `"644"` or `"0755"`.
+    ///
+    /// The setuid, setgid and sticky bits may be specified as a fourth (high) octal digit,
+    /// e.g. `"4755"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is empty, contains anything other than octal digits,
+    /// or specifies bits above `0o7777`.
+    pub fn from_octal_str(s: &str) -> Result<Self, ParsePermissionsError> {
+        if s.is_empty() || !s.bytes().all(|b| matches!(b, b'0'..=b'7')) {
+            return Err(ParsePermissionsError);
+        }
+        match u16::from_str_radix(s, 8) {
+            Ok(mode) if mode <= 0o7777 => Ok(Self::from_bits_retain(mode)),
+            _ => Err(ParsePermissionsError),
+        }
+    }
+
+    /// Returns the permissions as an octal mode, like `0o644`, including the setuid, setgid and
+    /// sticky bits.
+    ///
+    /// Any bits above `0o7777` are ignored.
+    #[must_use]
+    pub const fn to_octal(self) -> u16 {
+        self.bits() & 0o7777
+    }
+
+    /// Formats the permissions as a four digit octal string, like `"0644"` or `"4755"`.
+    ///
+    /// The high digit holds the setuid, setgid and sticky bits. This is the inverse of
+    /// [`Self::from_octal_str`].
+    #[must_use]
+    pub fn to_octal_string(self) -> String {
+        format!("{:04o}", self.to_octal())
+    }
+
     pub const fn to_str(self) -> PermissionsStr {
         let mut bytes = [0xFF; 3 * 3];
 
@@ -0,0 +1,132 @@
+use crate::{File, FileType, Inode, InodeRef, Permissions};
+use std::collections::HashMap;
+
+/// A mapping from numeric user and group ids to names.
+///
+/// The archive only stores numeric ids, this allows callers to plug in their own source of
+/// names (e.g. `/etc/passwd` and `/etc/group`) when displaying files.
+pub trait IdResolver {
+    /// Returns the name of the user with the given id, if known.
+    fn user_name(&self, uid: u32) -> Option<&str>;
+
+    /// Returns the name of the group with the given id, if known.
+    fn group_name(&self, gid: u32) -> Option<&str>;
+}
+
+/// A simple [`IdResolver`] backed by maps of user and group names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdNames {
+    /// Map from user id to user name.
+    pub users: HashMap<u32, String>,
+    /// Map from group id to group name.
+    pub groups: HashMap<u32, String>,
+}
+
+impl IdResolver for IdNames {
+    fn user_name(&self, uid: u32) -> Option<&str> {
+        self.users.get(&uid).map(String::as_str)
+    }
+
+    fn group_name(&self, gid: u32) -> Option<&str> {
+        self.groups.get(&gid).map(String::as_str)
+    }
+}
+
+/// A snapshot of the metadata of a file in a squashfs archive.
+///
+/// Unlike [`File`], this does not borrow from the archive, so it can be kept around after the
+/// file has been closed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Metadata {
+    file_type: Option<FileType>,
+    permissions: Permissions,
+    inode: Inode,
+    inode_ref: InodeRef,
+    size: u64,
+    uid: u32,
+    gid: u32,
+    modified_time: u32,
+    hard_link_count: u32,
+}
+
+impl Metadata {
+    pub(crate) fn new(file: &File<'_>) -> Self {
+        Self {
+            file_type: file.file_type(),
+            permissions: file.permissions(),
+            inode: file.inode(),
+            inode_ref: file.inode_ref(),
+            size: file.size(),
+            uid: file.uid(),
+            gid: file.gid(),
+            modified_time: file.modified_time(),
+            hard_link_count: file.hard_link_count(),
+        }
+    }
+
+    /// Returns the type of the file.
+    #[must_use]
+    pub fn file_type(&self) -> Option<FileType> {
+        self.file_type
+    }
+
+    /// Returns the permissions of the file.
+    #[must_use]
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// Returns the inode number of the file.
+    #[must_use]
+    pub fn inode(&self) -> Inode {
+        self.inode
+    }
+
+    /// Returns the inode reference of the file.
+    #[must_use]
+    pub fn inode_ref(&self) -> InodeRef {
+        self.inode_ref
+    }
+
+    /// Returns the size of the file. 0 if the file has no size.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the owner user id of the file.
+    #[must_use]
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Returns the owner group id of the file.
+    #[must_use]
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Returns the name of the owner of the file, as resolved by `resolver`.
+    #[must_use]
+    pub fn owner_name<'r, R: IdResolver + ?Sized>(&self, resolver: &'r R) -> Option<&'r str> {
+        resolver.user_name(self.uid)
+    }
+
+    /// Returns the name of the owning group of the file, as resolved by `resolver`.
+    #[must_use]
+    pub fn group_name<'r, R: IdResolver + ?Sized>(&self, resolver: &'r R) -> Option<&'r str> {
+        resolver.group_name(self.gid)
+    }
+
+    /// Returns the modification time, in seconds since the Unix epoch.
+    #[must_use]
+    pub fn modified_time(&self) -> u32 {
+        self.modified_time
+    }
+
+    /// Returns the hard link count of the inode.
+    #[must_use]
+    pub fn hard_link_count(&self) -> u32 {
+        self.hard_link_count
+    }
+}
@@ -1,8 +1,9 @@
 use crate::archive::Archive;
 use crate::utils::small_c_string::run_with_cstr;
-use crate::{error, File, FileType, Inode, InodeRef};
-use bstr::BStr;
+use crate::{error, Error, File, FileType, Inode, InodeRef};
+use bstr::{BStr, BString};
 use sqsh_sys as ffi;
+use std::collections::VecDeque;
 use std::ffi::{c_char, CStr};
 use std::fmt;
 use std::ptr::NonNull;
@@ -28,6 +29,87 @@ impl Archive<'_> {
         };
         Ok(unsafe { PathResolver::new(walker) })
     }
+
+    /// Resolve a path to an inode reference without opening the file.
+    ///
+    /// Like [`Self::open`], this will follow symlinks unless disabled for the archive, the inode
+    /// reference is that of the target. The reference can later be opened with
+    /// [`Self::open_ref`], to resolve a path once and open it many times.
+    pub fn path_to_ref(&self, path: &str) -> error::Result<InodeRef> {
+        let mut resolver = self.path_resolver()?;
+        resolver.resolve_path(path, self.inner.follow_symlinks)?;
+        Ok(resolver.current_dir_inode_ref())
+    }
+
+    /// Resolve a path to its canonical form, following all symlinks.
+    ///
+    /// This is similar to `realpath(3)`: the returned path is absolute (starts with `/`), and
+    /// contains no `.` or `..` segments, repeated slashes, or symlinks.
+    ///
+    /// Returns an error if any part of the path does not exist, if a non-final segment is not a
+    /// directory, or if more symlinks than the configured maximum symlink depth are followed
+    /// (for example, because of a symlink loop).
+    pub fn realpath(&self, path: &str) -> error::Result<BString> {
+        let mut max_symlinks =
+            unsafe { (*ffi::sqsh_archive_config(self.inner.as_ptr())).max_symlink_depth };
+        if max_symlinks == 0 {
+            max_symlinks = DEFAULT_MAX_SYMLINK_DEPTH;
+        }
+
+        let mut resolver = self.path_resolver()?;
+        let mut resolved: Vec<BString> = Vec::new();
+        let mut remaining: VecDeque<BString> = split_path(path.as_bytes()).collect();
+        let mut symlinks_followed = 0;
+
+        while let Some(segment) = remaining.pop_front() {
+            if segment == ".." {
+                // `..` of the root is the root itself
+                if resolved.pop().is_some() {
+                    resolver.up()?;
+                }
+                continue;
+            }
+
+            resolver.advance_lookup(&segment)?;
+            if resolver.current_file_type() == Some(FileType::Symlink) {
+                symlinks_followed += 1;
+                if symlinks_followed > max_symlinks {
+                    return Err(Error::Sqsh(
+                        ffi::SqshError::SQSH_ERROR_TOO_MANY_SYMLINKS_FOLLOWED,
+                    ));
+                }
+                let link = resolver.open()?;
+                let target = link.symlink_path().unwrap_or_default();
+                if target.starts_with(b"/") {
+                    resolver.reset_to_root()?;
+                    resolved.clear();
+                }
+                for target_segment in split_path(target).rev() {
+                    remaining.push_front(target_segment);
+                }
+                continue;
+            }
+
+            resolved.push(segment);
+            if !remaining.is_empty() {
+                resolver.down()?;
+            }
+        }
+
+        let mut result = BString::from("/");
+        result.extend_from_slice(&bstr::join("/", resolved));
+        Ok(result)
+    }
+}
+
+/// Used when the archive was not configured with a maximum symlink depth, matching libsqsh.
+const DEFAULT_MAX_SYMLINK_DEPTH: usize = 100;
+
+/// Split a path into segments, skipping empty and `.` segments
+fn split_path(path: &[u8]) -> impl DoubleEndedIterator<Item = BString> + '_ {
+    path.split(|&b| b == b'/')
+        .filter(|segment| !segment.is_empty() && *segment != b".")
+        .map(BString::from)
 }
 
 impl<'archive> PathResolver<'archive> {
@@ -111,6 +193,50 @@ impl<'archive> PathResolver<'archive> {
         }
     }
 
+    /// Looks up a relative path, starting in the current directory.
+    ///
+    /// The path is split on `/`, and each segment is looked up in turn, descending into each
+    /// directory along the way. On success, the resolver is positioned on the final entry, as
+    /// if by [`Self::advance_lookup`]. Empty segments are ignored, so an empty path leaves the
+    /// resolver where it is.
+    ///
+    /// Unlike [`Self::resolve_path`], segments are looked up literally: `.` and `..` have no
+    /// special meaning, and symlinks are never followed, so descending through a symlink (or
+    /// any other non-directory) fails.
+    pub fn advance_lookup_path(&mut self, path: &[u8]) -> error::Result<()> {
+        let mut segments = path.split(|&b| b == b'/').filter(|s| !s.is_empty());
+        let Some(first) = segments.next() else {
+            return Ok(());
+        };
+        self.advance_lookup(first)?;
+        for segment in segments {
+            self.down()?;
+            self.advance_lookup(segment)?;
+        }
+        Ok(())
+    }
+
+    /// Looks up an entry in the current directory, ignoring ASCII case.
+    ///
+    /// Names are compared as raw bytes, with only ASCII letters folded: no unicode case folding
+    /// is attempted, to avoid ambiguity. On success, the resolver is positioned on the first
+    /// matching entry.
+    ///
+    /// Unlike [`Self::advance_lookup`], this cannot make use of the directory index, and is
+    /// O(n) in the number of entries in the directory.
+    pub fn advance_lookup_ci(&mut self, name: &[u8]) -> error::Result<()> {
+        self.revert()?;
+        while self.advance()? {
+            if self
+                .current_name()
+                .is_some_and(|current| current.eq_ignore_ascii_case(name))
+            {
+                return Ok(());
+            }
+        }
+        Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE))
+    }
+
     /// Resets the resolver to the root directory.
     pub fn reset_to_root(&mut self) -> error::Result<()> {
         let err = unsafe { ffi::sqsh_path_resolver_to_root(self.inner.as_ptr()) };
@@ -1,32 +1,100 @@
-use crate::{error, Error, File};
+use crate::{error, Archive, Error, File, FileType};
+use bstr::BString;
 use sqsh_sys as ffi;
 use std::io;
-use std::io::BufRead;
-use std::marker::PhantomData;
+use std::io::{BufRead, Read};
 use std::ptr::NonNull;
+use std::sync::Arc;
 
 pub struct Reader<'file> {
     inner: NonNull<ffi::SqshFileIterator>,
+    file: ReaderFile<'file>,
     consumed: usize,
-    _marker: PhantomData<&'file File<'file>>,
+    position: u64,
+}
+
+// The iterator refers to the file, so the file must outlive it
+enum ReaderFile<'file> {
+    Borrowed(&'file File<'file>),
+    Shared(Arc<File<'file>>),
 }
 
 impl<'file> Reader<'file> {
-    pub(crate) unsafe fn new(inner: NonNull<ffi::SqshFileIterator>) -> Self {
+    pub(crate) unsafe fn new(
+        file: &'file File<'file>,
+        inner: NonNull<ffi::SqshFileIterator>,
+    ) -> Self {
         Self {
             inner,
+            file: ReaderFile::Borrowed(file),
             consumed: 0,
-            _marker: PhantomData,
+            position: 0,
         }
     }
 
+    pub(crate) fn new_shared(file: Arc<File<'file>>) -> error::Result<Self> {
+        let inner = file.iterator_raw()?;
+        Ok(Self {
+            inner,
+            file: ReaderFile::Shared(file),
+            consumed: 0,
+            position: 0,
+        })
+    }
+
+    /// Create a new, independent reader of the same file, at the same position.
+    ///
+    /// Reading from the returned reader does not affect the position of this reader, and
+    /// vice-versa.
+    pub fn try_clone(&self) -> error::Result<Reader<'file>> {
+        let mut reader = match &self.file {
+            ReaderFile::Borrowed(file) => file.reader()?,
+            ReaderFile::Shared(file) => Reader::new_shared(Arc::clone(file))?,
+        };
+        reader.skip(self.position)?;
+        Ok(reader)
+    }
+
+    /// Returns the current position in the file.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the number of bytes from the current position to the end of the file.
+    #[must_use]
+    pub fn remaining(&self) -> u64 {
+        self.file().size().saturating_sub(self.position)
+    }
+
     #[must_use]
     pub fn block_size(&self) -> usize {
         unsafe { ffi::sqsh_file_iterator_block_size(self.inner.as_ptr()) }
     }
 
+    /// Read all remaining bytes, from the current position to the end of the file.
+    pub fn read_to_vec(&mut self) -> error::Result<Vec<u8>> {
+        let mut dst = Vec::with_capacity(usize::try_from(self.remaining())?);
+        loop {
+            let buf = self.fill_buf_raw()?;
+            if buf.is_empty() {
+                break;
+            }
+            dst.extend_from_slice(buf);
+            let len = buf.len();
+            self.consume(len);
+        }
+        Ok(dst)
+    }
+
     /// Skip `n` bytes in the file.
+    ///
+    /// Skipping 0 bytes is a no-op.
     pub fn skip(&mut self, mut n: u64) -> error::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        let new_position = self.position.saturating_add(n);
         // Offset is measured from the _start_ of the current block
         n = n.saturating_add(self.consumed.try_into().unwrap());
         self.consumed = 0;
@@ -45,12 +113,80 @@ impl<'file> Reader<'file> {
                 return Err(error::new(err));
             }
             debug_assert!(self.current_chunk_size() >= offset_remaining);
-            self.consume(offset_remaining);
+            self.consumed += offset_remaining;
         }
 
+        self.position = new_position;
         Ok(())
     }
 
+    /// Returns the number of bytes of the current block which have been consumed.
+    #[must_use]
+    pub fn bytes_consumed_in_block(&self) -> usize {
+        self.consumed.min(self.current_chunk_size())
+    }
+
+    /// Skip the rest of the current block.
+    ///
+    /// Returns the number of bytes skipped, the next read will start at the beginning of the
+    /// next block. If the current block has been fully consumed (or nothing has been read yet),
+    /// this does nothing and returns 0.
+    pub fn skip_to_next_block(&mut self) -> error::Result<u64> {
+        let remaining = self.current_chunk_size() - self.bytes_consumed_in_block();
+        self.consume(remaining);
+        Ok(remaining as u64)
+    }
+
+    /// Advance to the start of the next run of nonzero bytes.
+    ///
+    /// Skips any zero bytes at the current position, and returns the number of bytes skipped.
+    /// Afterwards, the reader is positioned at a nonzero byte, or at the end of the file. This
+    /// allows extracting files as sparse files: seek the output forward by the returned amount
+    /// instead of writing zeros.
+    ///
+    /// libsqsh does not expose which blocks are stored sparse in the archive, so zeros are
+    /// detected by scanning the data: any run of zeros is skipped, whether or not it was stored
+    /// as a sparse block. Sparse blocks are still read as zeros, so this saves writing the zeros,
+    /// not reading them.
+    pub fn next_nonzero_run(&mut self) -> error::Result<u64> {
+        let mut skipped = 0;
+        loop {
+            let available = self.fill_buf_raw()?;
+            if available.is_empty() {
+                break;
+            }
+            let (done, zeros) = match available.iter().position(|&b| b != 0) {
+                Some(i) => (true, i),
+                None => (false, available.len()),
+            };
+            self.consume(zeros);
+            skipped += zeros as u64;
+            if done {
+                break;
+            }
+        }
+        Ok(skipped)
+    }
+
+    /// Limit the reader to at most `limit` more bytes.
+    ///
+    /// This is like [`Read::take`], but the returned reader also implements [`BufRead`], and
+    /// [`BufRead::fill_buf`] never returns data past the limit.
+    #[must_use]
+    pub fn take(self, limit: u64) -> TakeReader<'file> {
+        TakeReader {
+            reader: self,
+            limit,
+        }
+    }
+
+    fn file(&self) -> &File<'file> {
+        match &self.file {
+            ReaderFile::Borrowed(file) => file,
+            ReaderFile::Shared(file) => file,
+        }
+    }
+
     fn current_chunk_size(&self) -> usize {
         unsafe { ffi::sqsh_file_iterator_size(self.inner.as_ptr()) }
     }
@@ -76,10 +212,272 @@ impl<'file> Reader<'file> {
         let data = unsafe { std::slice::from_raw_parts(data_ptr, size) };
         Ok(&data[self.consumed..])
     }
+
+    pub(crate) fn read_until_raw(&mut self, byte: u8, buf: &mut Vec<u8>) -> error::Result<usize> {
+        let mut read = 0;
+        loop {
+            let available = self.fill_buf_raw()?;
+            let (done, used) = match available.iter().position(|&b| b == byte) {
+                Some(i) => {
+                    buf.extend_from_slice(&available[..=i]);
+                    (true, i + 1)
+                }
+                None => {
+                    buf.extend_from_slice(available);
+                    (available.is_empty(), available.len())
+                }
+            };
+            self.consume(used);
+            read += used;
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+}
+
+/// A reader which limits the ratio of decompressed to compressed data.
+///
+/// Created by [`File::reader_guarded`].
+pub struct GuardedReader<'file> {
+    reader: Reader<'file>,
+    max_ratio: u64,
+    // The number of blocks, from the start of the file, which have been counted
+    blocks_checked: u64,
+    compressed: u64,
+    decompressed: u64,
+}
+
+// The bytes of the inode used to store the size of each block
+const BLOCK_SIZE_ENTRY_LEN: u64 = 4;
+
+impl<'file> GuardedReader<'file> {
+    pub(crate) fn new(reader: Reader<'file>, max_ratio: u32) -> Self {
+        Self {
+            reader,
+            max_ratio: max_ratio.into(),
+            blocks_checked: 0,
+            compressed: 0,
+            decompressed: 0,
+        }
+    }
+
+    /// Returns the current position in the file.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Count every block up to the current position, and check the ratio.
+    fn check(&mut self) -> error::Result<()> {
+        let block_size = self.reader.block_size() as u64;
+        if block_size == 0 {
+            return Ok(());
+        }
+        let file = self.reader.file();
+        let size = file.size();
+        let full_blocks = u64::from(file.raw_block_count());
+        let last_block = (self.reader.position() / block_size).min(size.div_ceil(block_size));
+        while self.blocks_checked <= last_block && self.blocks_checked * block_size < size {
+            let index = self.blocks_checked;
+            let decompressed = block_size.min(size - index * block_size);
+            let compressed = if index < full_blocks {
+                file.stored_block_size(u32::try_from(index)?) + BLOCK_SIZE_ENTRY_LEN
+            } else {
+                // The tail of the file, in a fragment
+                decompressed
+            };
+            self.decompressed += decompressed;
+            self.compressed += compressed;
+            self.blocks_checked += 1;
+        }
+        if self.decompressed > self.compressed.saturating_mul(self.max_ratio) {
+            return Err(Error::Sqsh(ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS));
+        }
+        Ok(())
+    }
+}
+
+impl Read for GuardedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let src = self.fill_buf()?;
+        let len = src.len().min(buf.len());
+        buf[..len].copy_from_slice(&src[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for GuardedReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.check()?;
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt);
+    }
+}
+
+/// A reader which reads at most a fixed number of bytes.
+///
+/// Created by [`Reader::take`].
+pub struct TakeReader<'file> {
+    reader: Reader<'file>,
+    limit: u64,
+}
+
+impl<'file> TakeReader<'file> {
+    /// Returns the number of bytes which can still be read before the limit is reached.
+    #[must_use]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the current position in the file.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Returns the underlying reader, which may be positioned anywhere up to the limit.
+    #[must_use]
+    pub fn into_inner(self) -> Reader<'file> {
+        self.reader
+    }
+}
+
+impl Read for TakeReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let src = self.fill_buf()?;
+        let len = src.len().min(buf.len());
+        buf[..len].copy_from_slice(&src[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for TakeReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+        let src = self.reader.fill_buf()?;
+        let len = usize::try_from(self.limit).map_or(src.len(), |limit| limit.min(src.len()));
+        Ok(&src[..len])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = (amt as u64).min(self.limit);
+        self.limit -= amt;
+        self.reader.consume(amt as usize);
+    }
+}
+
+/// A reader of the concatenated contents of the files in a directory.
+///
+/// Created by [`Archive::open_concat`].
+pub struct ConcatReader<'archive> {
+    parts: std::vec::IntoIter<File<'archive>>,
+    current: Option<Reader<'archive>>,
+}
+
+impl Archive<'_> {
+    /// Open a reader of the contents of all regular files in a directory, concatenated.
+    ///
+    /// Files are read in order of their names (compared as bytes), so a payload split into
+    /// parts like `part.00`, `part.01`, ... is read back as a single stream. Entries of the
+    /// directory which are not regular files (including symlinks) are skipped. The directory
+    /// is listed up front, but each part is only read once the previous part is finished.
+    pub fn open_concat(&self, dir: &str) -> error::Result<ConcatReader<'_>> {
+        let mut dir = self.read_dir(dir)?;
+        let mut parts = Vec::new();
+        while let Some(entry) = dir.advance()? {
+            if entry.file_type() == Some(FileType::File) {
+                parts.push((BString::from(entry.name()), entry.inode_ref()));
+            }
+        }
+        parts.sort();
+        let parts = parts
+            .into_iter()
+            .map(|(_, inode_ref)| self.open_ref(inode_ref))
+            .collect::<error::Result<Vec<_>>>()?;
+        Ok(ConcatReader {
+            parts: parts.into_iter(),
+            current: None,
+        })
+    }
+}
+
+impl Read for ConcatReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if let Some(reader) = &mut self.current {
+                let len = reader.read(buf)?;
+                if len > 0 {
+                    return Ok(len);
+                }
+            }
+            match self.parts.next() {
+                Some(file) => self.current = Some(file.into_reader()?),
+                None => {
+                    self.current = None;
+                    return Ok(0);
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the lines of a file.
+///
+/// Created by [`File::lines`].
+pub struct Lines<'file> {
+    reader: Reader<'file>,
+}
+
+impl<'file> Lines<'file> {
+    pub(crate) fn new(reader: Reader<'file>) -> Self {
+        Self { reader }
+    }
+}
+
+impl Iterator for Lines<'_> {
+    type Item = error::Result<BString>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        match self.reader.read_until_raw(b'\n', &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                    if line.last() == Some(&b'\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(BString::from(line)))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
 }
 
 impl<'file> io::Read for Reader<'file> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Don't decompress the next block just to copy nothing out of it
+        if buf.is_empty() {
+            return Ok(0);
+        }
         let src = self.fill_buf()?;
         let len = src.len().min(buf.len());
         buf[..len].copy_from_slice(&src[..len]);
@@ -95,6 +493,11 @@ impl<'file> BufRead for Reader<'file> {
 
     fn consume(&mut self, amt: usize) {
         self.consumed += amt;
+        self.position += amt as u64;
+    }
+
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.read_until_raw(byte, buf).map_err(Error::into_io_error)
     }
 }
 
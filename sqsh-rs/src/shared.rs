@@ -0,0 +1,84 @@
+use crate::{error, Archive, Reader};
+use std::io::{self, BufRead, Read};
+use std::ops::Deref;
+
+/// A reference counted handle to an archive, which can be cheaply cloned and shared between
+/// threads.
+///
+/// Unlike a plain [`Archive`], readers created from a `SharedArchive` keep the archive alive
+/// themselves, so they can be moved to other threads without any borrowing arrangement.
+#[derive(Debug, Clone)]
+pub struct SharedArchive {
+    archive: Archive<'static>,
+}
+
+impl SharedArchive {
+    /// Wrap an archive to be shared.
+    #[must_use]
+    pub fn new(archive: Archive<'static>) -> Self {
+        Self { archive }
+    }
+
+    /// Open a reader for the file at the given path, following symlinks.
+    ///
+    /// The file of the returned reader holds a clone of the archive, so it does not borrow from
+    /// this handle.
+    pub fn open_reader(&self, path: &str) -> error::Result<OwnedReader> {
+        let file = self.archive.open_owned(path)?;
+        Ok(OwnedReader {
+            reader: file.into_reader()?,
+        })
+    }
+}
+
+impl From<Archive<'static>> for SharedArchive {
+    fn from(archive: Archive<'static>) -> Self {
+        Self::new(archive)
+    }
+}
+
+impl Deref for SharedArchive {
+    type Target = Archive<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.archive
+    }
+}
+
+/// A reader of a file, which keeps its archive alive.
+///
+/// Created by [`SharedArchive::open_reader`]. This can be moved freely between threads.
+pub struct OwnedReader {
+    reader: Reader<'static>,
+}
+
+impl OwnedReader {
+    /// Returns the current position in the file.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Skip `n` bytes in the file.
+    ///
+    /// See [`Reader::skip`].
+    pub fn skip(&mut self, n: u64) -> error::Result<()> {
+        self.reader.skip(n)
+    }
+}
+
+impl Read for OwnedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl BufRead for OwnedReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt);
+    }
+}
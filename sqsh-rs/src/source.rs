@@ -52,6 +52,9 @@ pub unsafe trait Source {
     const BLOCK_SIZE_HINT: usize;
 
     /// Retrieve the size of the archive.
+    ///
+    /// This may be called more than once while opening the archive, and should return the same
+    /// size each time.
     fn size(&mut self) -> crate::error::Result<usize>;
 
     /// Map a section of a source into memory.
@@ -85,6 +88,81 @@ pub unsafe trait Source {
     unsafe fn unmap(&mut self, ptr: *mut u8, size: usize) -> crate::error::Result<()>;
 }
 
+/// A source which owns an in-memory buffer containing the whole archive.
+pub(crate) struct OwnedSource<T> {
+    data: T,
+}
+
+impl<T: AsRef<[u8]>> OwnedSource<T> {
+    pub(crate) fn new(data: T) -> Self {
+        Self { data }
+    }
+}
+
+unsafe impl<T: AsRef<[u8]>> Source for OwnedSource<T> {
+    // The whole archive is already in memory, mapping is just pointer arithmetic
+    const BLOCK_SIZE_HINT: usize = 1024 * 1024;
+
+    fn size(&mut self) -> crate::error::Result<usize> {
+        Ok(self.data.as_ref().len())
+    }
+
+    unsafe fn map(&mut self, offset: usize, size: usize) -> crate::error::Result<*mut u8> {
+        let data = self.data.as_ref();
+        match offset.checked_add(size) {
+            Some(end) if end <= data.len() => {}
+            _ => return Err(crate::Error::Sqsh(ffi::SqshError::SQSH_ERROR_OUT_OF_BOUNDS)),
+        }
+        // libsqsh never writes through mapped pointers
+        Ok(data.as_ptr().add(offset).cast_mut())
+    }
+
+    unsafe fn unmap(&mut self, _ptr: *mut u8, _size: usize) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
+
+/// A source which reads from a file with positioned reads (`pread`), and owns the file.
+#[cfg(unix)]
+pub(crate) struct FdSource {
+    file: std::fs::File,
+}
+
+#[cfg(unix)]
+impl FdSource {
+    pub(crate) fn new(fd: std::os::fd::OwnedFd) -> Self {
+        Self { file: fd.into() }
+    }
+}
+
+#[cfg(unix)]
+unsafe impl Source for FdSource {
+    const BLOCK_SIZE_HINT: usize = 128 * 1024;
+
+    fn size(&mut self) -> crate::error::Result<usize> {
+        let metadata = self
+            .file
+            .metadata()
+            .map_err(|_| crate::Error::Sqsh(ffi::SqshError::SQSH_ERROR_MAPPER_INIT))?;
+        Ok(usize::try_from(metadata.len())?)
+    }
+
+    unsafe fn map(&mut self, offset: usize, size: usize) -> crate::error::Result<*mut u8> {
+        use std::os::unix::fs::FileExt;
+
+        let mut buf = vec![0; size].into_boxed_slice();
+        self.file
+            .read_exact_at(&mut buf, u64::try_from(offset)?)
+            .map_err(|_| crate::Error::Sqsh(ffi::SqshError::SQSH_ERROR_MAPPER_MAP))?;
+        Ok(Box::into_raw(buf).cast::<u8>())
+    }
+
+    unsafe fn unmap(&mut self, ptr: *mut u8, size: usize) -> crate::error::Result<()> {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, size)));
+        Ok(())
+    }
+}
+
 pub(crate) fn to_ptr<S: Source>(source: S) -> *mut c_void {
     let s_ptr = if size_of::<S>() == 0 {
         NonNull::dangling().as_ptr()
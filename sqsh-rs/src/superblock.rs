@@ -1,4 +1,4 @@
-use crate::{error, Archive, InodeRef};
+use crate::{error, Archive, Inode, InodeRef};
 use bitflags::bitflags;
 use sqsh_sys as ffi;
 use std::fmt;
@@ -35,6 +35,16 @@ impl Compression {
         id: ffi::SqshSuperblockCompressionId::SQSH_COMPRESSION_ZSTD,
     };
 
+    /// The raw compression id, as stored in the superblock.
+    ///
+    /// This is useful to report compressors which are not known, where [`Self::name`] returns
+    /// `None`.
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        // The id is stored as a u16 in the superblock
+        self.id.0 as u16
+    }
+
     #[must_use]
     pub fn name(&self) -> Option<&'static str> {
         Some(match *self {
@@ -54,12 +64,22 @@ impl fmt::Debug for Compression {
         let name = self.name();
         let value: &dyn fmt::Debug = match &name {
             Some(name) => name,
-            None => &self.id,
+            None => &self.id(),
         };
         f.debug_tuple("Compression").field(value).finish()
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Compression {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.name() {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_u16(self.id()),
+        }
+    }
+}
+
 /// Information about the compression options used in an archive.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum CompressionOptions {
@@ -76,6 +96,8 @@ pub enum CompressionOptions {
         version: u32,
         flags: Lz4Flags,
     },
+    /// The squashfs zstd options block only stores the compression level: other zstd parameters
+    /// (e.g. the window log) are derived from the level when compressing, and are not recorded.
     Zstd {
         compression_level: u32,
     },
@@ -83,9 +105,18 @@ pub enum CompressionOptions {
         algorithm: LzoAlgorithm,
         compression_level: u32,
     },
+    /// The archive has compression options, but they can't be decoded for this compressor.
+    Unknown {
+        compression: Compression,
+    },
 }
 
 bitflags! {
+    /// The zlib strategies tried when compressing with gzip.
+    ///
+    /// The archive stores these as a set of flags: when multiple strategies are set, each block
+    /// was compressed with every strategy, and the smallest result kept. Use `iter()` to list
+    /// the individual strategies.
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub struct GzipStrategies: u16 {
         const DEFAULT = 1 << 0;
@@ -97,6 +128,11 @@ bitflags! {
         const _ = !0;
     }
 
+    /// The BCJ filters tried when compressing with xz.
+    ///
+    /// The archive stores these as a set of flags, not an ordered filter chain: each block was
+    /// compressed with no BCJ filter and with each of these filters on its own, and the smallest
+    /// result kept. Use `iter()` to list the individual filters.
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub struct XzFilters: u16 {
         const X86 = 1 << 0;
@@ -163,6 +199,112 @@ impl Archive<'_> {
         unsafe { Superblock::new(ffi::sqsh_archive_superblock(self.inner.as_ptr())) }
     }
 
+    /// Returns an owned summary of the superblock of the archive.
+    ///
+    /// See [`Superblock::info`].
+    #[must_use]
+    pub fn info(&self) -> ArchiveInfo {
+        self.superblock().info()
+    }
+
+    /// Retrieves the block size of the archive.
+    ///
+    /// Shortcut for `archive.superblock().block_size()`.
+    #[must_use]
+    pub fn block_size(&self) -> u32 {
+        self.superblock().block_size()
+    }
+
+    /// Retrieves the compression type of the archive.
+    ///
+    /// Shortcut for `archive.superblock().compression_type()`.
+    #[must_use]
+    pub fn compression_type(&self) -> Compression {
+        self.superblock().compression_type()
+    }
+
+    /// Retrieves the number of inodes in the archive.
+    ///
+    /// Shortcut for `archive.superblock().inode_count()`.
+    #[must_use]
+    pub fn inode_count(&self) -> u32 {
+        self.superblock().inode_count()
+    }
+
+    /// Retrieves the modification time of the archive, as seconds since the Unix epoch.
+    ///
+    /// Shortcut for `archive.superblock().modification_time()`.
+    #[must_use]
+    pub fn modification_time(&self) -> u32 {
+        self.superblock().modification_time()
+    }
+
+    /// Retrieves the number of bytes used by the archive.
+    ///
+    /// Shortcut for `archive.superblock().bytes_used()`.
+    #[must_use]
+    pub fn bytes_used(&self) -> u64 {
+        self.superblock().bytes_used()
+    }
+
+    /// Returns true if the archive has an export table.
+    ///
+    /// Shortcut for `archive.superblock().has_export_table()`. If this is false,
+    /// [`Archive::export_table`] will return an error.
+    #[must_use]
+    pub fn has_export_table(&self) -> bool {
+        self.superblock().has_export_table()
+    }
+
+    /// Returns true if the archive has an xattr table.
+    ///
+    /// Shortcut for `archive.superblock().has_xattr_table()`.
+    #[must_use]
+    pub fn has_xattr_table(&self) -> bool {
+        self.superblock().has_xattr_table()
+    }
+
+    /// Retrieves the compressor, compression options, and block size of the archive.
+    ///
+    /// See [`Archive::compression_options`] for how the options are decoded.
+    pub fn compression_spec(&self) -> error::Result<CompressionSpec> {
+        Ok(CompressionSpec {
+            compression: self.compression_type(),
+            options: self.compression_options()?,
+            block_size: self.block_size(),
+        })
+    }
+
+    /// Retrieves the name of the compressor used by the archive.
+    ///
+    /// Shortcut for `archive.compression_type().name()`. Returns `None` if the compressor isn't
+    /// known, use [`Compression::id`] to report it instead.
+    #[must_use]
+    pub fn compressor_name(&self) -> Option<&'static str> {
+        self.compression_type().name()
+    }
+
+    /// Retrieves the block size data blocks were compressed with.
+    ///
+    /// Unlike the compression options, this is always recorded in the superblock, so it's
+    /// available even when [`Archive::compression_options`] returns `None`. Together with
+    /// [`Archive::compressor_name`], it's enough to report a summary for archives without an
+    /// options block.
+    #[must_use]
+    pub fn effective_block_size(&self) -> u32 {
+        self.block_size()
+    }
+
+    /// Retrieves the compression options of the archive.
+    ///
+    /// Returns `None` if the archive has no compression options. If the archive has compression
+    /// options which can't be decoded for its compressor, returns
+    /// [`CompressionOptions::Unknown`].
+    ///
+    /// Without an options block, the archive was usually written with the compressor's default
+    /// settings, but nothing records which: the exact levels, window sizes or filters used are
+    /// unknown. Use [`Archive::compressor_name`] and [`Archive::effective_block_size`] to
+    /// describe the compression of such archives.
     pub fn compression_options(&self) -> error::Result<Option<CompressionOptions>> {
         struct RawCompressionOptions(NonNull<ffi::SqshCompressionOptions>);
         impl Drop for RawCompressionOptions {
@@ -175,6 +317,18 @@ impl Archive<'_> {
         if !superblock.has_compression_options() {
             return Ok(None);
         }
+        let compression = superblock.compression_type();
+        // Don't ask libsqsh to parse options blocks it has no layout for
+        if !matches!(
+            compression,
+            Compression::GZIP
+                | Compression::XZ
+                | Compression::LZ4
+                | Compression::ZSTD
+                | Compression::LZO
+        ) {
+            return Ok(Some(CompressionOptions::Unknown { compression }));
+        }
         let compression_options = unsafe {
             let mut err = 0;
             let raw = ffi::sqsh_compression_options_new(self.inner.as_ptr(), &mut err);
@@ -185,7 +339,7 @@ impl Archive<'_> {
             RawCompressionOptions(raw)
         };
 
-        Ok(Some(match superblock.compression_type() {
+        Ok(Some(match compression {
             Compression::GZIP => CompressionOptions::Gzip {
                 compression_level: unsafe {
                     ffi::sqsh_compression_options_gzip_compression_level(
@@ -234,7 +388,7 @@ impl Archive<'_> {
                     )
                 },
             },
-            _ => return Ok(None),
+            _ => unreachable!("other compressors are handled above"),
         }))
     }
 }
@@ -263,6 +417,13 @@ impl<'archive> Superblock<'archive> {
         unsafe { ffi::sqsh_superblock_inode_count(self.inner) }
     }
 
+    /// Returns an iterator over all inode numbers in the archive.
+    ///
+    /// Inodes are numbered from 1 to [`Self::inode_count`] (inclusive).
+    pub fn inodes(&self) -> impl DoubleEndedIterator<Item = Inode> {
+        (1..=self.inode_count()).map(|i| Inode::new(i).unwrap())
+    }
+
     /// Retrieves the number of ids in an archive.
     #[must_use]
     pub fn id_count(&self) -> u16 {
@@ -294,6 +455,10 @@ impl<'archive> Superblock<'archive> {
             .then(|| unsafe { ffi::sqsh_superblock_fragment_table_start(self.inner) })
     }
 
+    // TODO: Archive::fragments, iterating the entries of the fragment table (start, compressed
+    //       and uncompressed size), once libsqsh exposes a way to read fragment table entries.
+    //       `sqsh_archive_fragment_table` only returns an opaque handle.
+
     /// Retrieves the start offset of the export table in a superblock context.
     #[must_use]
     pub fn export_table_start(&self) -> Option<u64> {
@@ -363,6 +528,12 @@ impl<'archive> Superblock<'archive> {
         unsafe { ffi::sqsh_superblock_block_size(self.inner) }
     }
 
+    /// Retrieves the log2 of the block size of an archive.
+    #[must_use]
+    pub fn block_log(&self) -> u16 {
+        self.block_size().trailing_zeros() as u16
+    }
+
     /// Retrieves the modification time of an archive, as seconds since the Unix epoch.
     #[must_use]
     pub fn modification_time(&self) -> u32 {
@@ -374,6 +545,86 @@ impl<'archive> Superblock<'archive> {
     pub fn bytes_used(&self) -> u64 {
         unsafe { ffi::sqsh_superblock_bytes_used(self.inner) }
     }
+
+    /// Returns an owned copy of the commonly used information in the superblock.
+    ///
+    /// Unlike `Superblock`, the result does not borrow from the archive.
+    #[must_use]
+    pub fn info(&self) -> ArchiveInfo {
+        ArchiveInfo {
+            compression: self.compression_type(),
+            version_major: self.version_major(),
+            version_minor: self.version_minor(),
+            block_size: self.block_size(),
+            modification_time: self.modification_time(),
+            bytes_used: self.bytes_used(),
+            inode_count: self.inode_count(),
+            id_count: self.id_count(),
+            fragment_entry_count: self.fragment_entry_count(),
+            has_fragments: self.has_fragments(),
+            has_export_table: self.has_export_table(),
+            has_xattr_table: self.has_xattr_table(),
+            has_compression_options: self.has_compression_options(),
+        }
+    }
+
+    /// Formats a human readable summary of the superblock.
+    ///
+    /// The layout follows the output of `unsquashfs -s`, so it can be used to build a compatible
+    /// report. Note that libsqsh does not expose the superblock flags, so the lines derived from
+    /// them (e.g. whether duplicates were removed, or which tables are compressed) are omitted,
+    /// and the creation time is printed as seconds since the Unix epoch.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut result = String::new();
+        self.write_summary(&mut result).unwrap();
+        result
+    }
+
+    fn write_summary(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let bytes_used = self.bytes_used();
+        let compression = self.compression_type();
+
+        writeln!(
+            f,
+            "Found a valid SQUASHFS {}:{} superblock.",
+            self.version_major(),
+            self.version_minor(),
+        )?;
+        writeln!(
+            f,
+            "Creation or last append time {}",
+            self.modification_time()
+        )?;
+        writeln!(
+            f,
+            "Filesystem size {bytes_used} bytes ({:.2} Kbytes / {:.2} Mbytes)",
+            bytes_used as f64 / 1024.0,
+            bytes_used as f64 / (1024.0 * 1024.0),
+        )?;
+        match compression.name() {
+            Some(name) => writeln!(f, "Compression {name}")?,
+            None => writeln!(f, "Compression unknown ({})", compression.id())?,
+        }
+        writeln!(f, "Block size {}", self.block_size())?;
+        if self.has_export_table() {
+            writeln!(f, "Filesystem is exportable via NFS")?;
+        } else {
+            writeln!(f, "Filesystem is not exportable via NFS")?;
+        }
+        if self.has_compression_options() {
+            writeln!(f, "Compressor options are present")?;
+        } else {
+            writeln!(f, "Compressor options are not present")?;
+        }
+        if !self.has_xattr_table() {
+            writeln!(f, "There are no Xattrs in the filesystem")?;
+        }
+        writeln!(f, "Number of fragments {}", self.fragment_entry_count())?;
+        writeln!(f, "Number of inodes {}", self.inode_count())?;
+        writeln!(f, "Number of ids {}", self.id_count())?;
+        Ok(())
+    }
 }
 
 impl<'archive> fmt::Debug for Superblock<'archive> {
@@ -402,3 +653,52 @@ impl<'archive> fmt::Debug for Superblock<'archive> {
             .finish()
     }
 }
+
+/// An owned summary of the superblock of an archive.
+///
+/// Created by [`Superblock::info`] or [`Archive::info`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct ArchiveInfo {
+    /// The compression used for the archive.
+    pub compression: Compression,
+    /// The major version of the squashfs format.
+    pub version_major: u16,
+    /// The minor version of the squashfs format.
+    pub version_minor: u16,
+    /// The size of data blocks in the archive.
+    pub block_size: u32,
+    /// The modification time of the archive, as seconds since the Unix epoch.
+    pub modification_time: u32,
+    /// The number of bytes used by the archive.
+    pub bytes_used: u64,
+    /// The number of inodes in the archive.
+    pub inode_count: u32,
+    /// The number of unique uids and gids in the archive.
+    pub id_count: u16,
+    /// The number of entries in the fragment table.
+    pub fragment_entry_count: u32,
+    /// Whether the archive has a fragment table.
+    pub has_fragments: bool,
+    /// Whether the archive has an export table.
+    pub has_export_table: bool,
+    /// Whether the archive has an xattr table.
+    pub has_xattr_table: bool,
+    /// Whether the archive has compression options.
+    pub has_compression_options: bool,
+}
+
+/// The settings needed to create an archive compressed the same way as an existing one.
+///
+/// Created by [`Archive::compression_spec`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CompressionSpec {
+    /// The compression used for the archive.
+    pub compression: Compression,
+    /// The compression options of the archive, if it has any.
+    pub options: Option<CompressionOptions>,
+    /// The size of data blocks in the archive.
+    pub block_size: u32,
+}
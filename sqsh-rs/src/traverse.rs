@@ -1,10 +1,15 @@
-use crate::{error, Archive, File, FileType};
-use bstr::BStr;
+use crate::{error, Archive, Error, File, FileType, Inode, InodeRef, Reader};
+use bstr::{BStr, BString};
 use sqsh_sys as ffi;
 use std::fmt;
 use std::iter::FusedIterator;
 use std::marker::PhantomData;
+use std::mem;
+use std::panic;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
 
 /// An efficient traversal of the archive.
 ///
@@ -14,12 +19,33 @@ use std::ptr::NonNull;
 /// implement the `Iterator` trait.
 pub struct Traversal<'archive> {
     inner: NonNull<ffi::SqshTreeTraversal>,
+    // Traversals which are paused at a symlink being followed, outermost first.
+    // `inner` is a traversal of the target of the last symlink.
+    outer: Vec<NonNull<ffi::SqshTreeTraversal>>,
+    // The targets of the followed symlinks, one for each traversal in `outer`.
+    // These must outlive the traversals over them.
+    followed: Vec<File<'archive>>,
+    // Inodes of the directories which have been entered but not yet exited, innermost last.
+    parents: Vec<Inode>,
+    // The parent of the current entry.
+    parent: Option<Inode>,
+    root: InodeRef,
+    root_inode: Inode,
+    // The root of the traversal, for traversals created with `File::into_traversal`.
+    // Must outlive all the traversals.
+    root_file: Option<File<'archive>>,
+    max_depth: Option<usize>,
+    follow_symlinks: bool,
+    follow_pending: bool,
+    visit_directories_once: bool,
     _marker: PhantomData<&'archive Archive<'archive>>,
 }
 
 #[derive(Copy, Clone)]
 pub struct Entry<'traversal, 'archive> {
     inner: &'traversal ffi::SqshTreeTraversal,
+    outer: &'traversal [NonNull<ffi::SqshTreeTraversal>],
+    parent: Option<Inode>,
     _marker: PhantomData<&'traversal Traversal<'archive>>,
 }
 
@@ -32,6 +58,7 @@ pub struct Path<'traversal> {
 #[derive(Debug, Clone)]
 pub struct PathSegments<'traversal> {
     inner: &'traversal ffi::SqshTreeTraversal,
+    outer: &'traversal [NonNull<ffi::SqshTreeTraversal>],
     depth: usize,
     index: usize,
 }
@@ -60,35 +87,430 @@ impl State {
 }
 
 impl<'archive> Traversal<'archive> {
-    pub(crate) unsafe fn new(inner: NonNull<ffi::SqshTreeTraversal>) -> Self {
+    pub(crate) unsafe fn new(
+        inner: NonNull<ffi::SqshTreeTraversal>,
+        root: InodeRef,
+        root_inode: Inode,
+    ) -> Self {
         Self {
             inner,
+            outer: Vec::new(),
+            followed: Vec::new(),
+            parents: Vec::new(),
+            parent: None,
+            root,
+            root_inode,
+            root_file: None,
+            max_depth: None,
+            follow_symlinks: false,
+            follow_pending: false,
+            visit_directories_once: false,
             _marker: PhantomData,
         }
     }
 
+    pub(crate) fn keep_root_file(&mut self, file: File<'archive>) {
+        self.root_file = Some(file);
+    }
+
     pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
         unsafe { ffi::sqsh_tree_traversal_set_max_depth(self.inner.as_ptr(), max_depth) }
     }
 
+    /// Set whether symlinks to directories should be followed.
+    ///
+    /// When enabled, a symlink is still visited as a normal entry, but if it resolves to a
+    /// directory, the traversal will then descend into the target directory, with paths of the
+    /// entries below it relative to the symlink. The target directory itself is not visited:
+    /// the symlink is only visited once, in the [`State::Normal`] state, so every
+    /// [`State::DirectorySecond`] entry still matches an earlier [`State::DirectoryFirst`] entry.
+    ///
+    /// Symlinks which cannot be resolved (e.g. dangling symlinks) are visited as normal entries.
+    /// To avoid infinite recursion, a symlink is not followed if its target is the root of the
+    /// traversal, or a directory which contains the symlink, either directly or through other
+    /// followed symlinks.
+    ///
+    /// Disabled by default.
+    pub fn set_follow_symlinks(&mut self, follow: bool) {
+        self.follow_symlinks = follow;
+    }
+
+    /// Set whether directories should only be visited once.
+    ///
+    /// When enabled, [`Self::advance`] never returns entries in the [`State::DirectorySecond`]
+    /// state: each directory is only visited before its contents.
+    ///
+    /// Disabled by default.
+    pub fn set_visit_directories_once(&mut self, once: bool) {
+        self.visit_directories_once = once;
+    }
+
     /// Attempt to advance the traversal to the next entry.
     pub fn advance(&mut self) -> error::Result<Option<Entry<'_, 'archive>>> {
+        loop {
+            if !self.advance_raw()? {
+                return Ok(None);
+            }
+            if !(self.visit_directories_once && self.current_entry().state().is_second_visit()) {
+                return Ok(Some(self.current_entry()));
+            }
+        }
+    }
+
+    /// Advance to the next entry, including second visits of directories.
+    ///
+    /// Returns `false` when the traversal is finished.
+    fn advance_raw(&mut self) -> error::Result<bool> {
+        if mem::take(&mut self.follow_pending) && self.follow_symlinks {
+            self.follow_current()?;
+        }
+        loop {
+            let mut err = 0;
+            let has_next = unsafe { ffi::sqsh_tree_traversal_next(self.inner.as_ptr(), &mut err) };
+            if err != 0 {
+                return Err(error::new(err));
+            }
+            if has_next {
+                if !self.outer.is_empty()
+                    && unsafe { ffi::sqsh_tree_traversal_depth(self.inner.as_ptr()) } == 0
+                {
+                    // The exit of a followed directory: there was no matching first visit, the
+                    // symlink was visited as a normal entry instead
+                    self.parents.pop();
+                    continue;
+                }
+                break;
+            }
+            // Done with the target of a symlink, continue with the traversal containing it
+            let Some(outer) = self.outer.pop() else {
+                return Ok(false);
+            };
+            unsafe { ffi::sqsh_tree_traversal_free(self.inner.as_ptr()) };
+            self.inner = outer;
+            self.followed.pop();
+        }
+        let file_type = unsafe { ffi::sqsh_tree_traversal_type(self.inner.as_ptr()) };
+        self.follow_pending =
+            self.follow_symlinks && file_type == ffi::SqshFileType::SQSH_FILE_TYPE_SYMLINK;
+        self.update_parents();
+        Ok(true)
+    }
+
+    fn update_parents(&mut self) {
+        let entry = self.current_entry();
+        match entry.state() {
+            State::Normal => self.parent = self.parents.last().copied(),
+            State::DirectoryFirst => {
+                let inode = match entry.directory_entry() {
+                    Some(dir_entry) => dir_entry.inode(),
+                    None => self.root_inode,
+                };
+                self.parent = self.parents.last().copied();
+                self.parents.push(inode);
+            }
+            State::DirectorySecond => {
+                self.parents.pop();
+                self.parent = self.parents.last().copied();
+            }
+        }
+    }
+
+    fn current_entry(&self) -> Entry<'_, 'archive> {
+        Entry {
+            inner: unsafe { self.inner.as_ref() },
+            outer: &self.outer,
+            parent: self.parent,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Start traversing the target of the current entry, which must be a symlink.
+    fn follow_current(&mut self) -> error::Result<()> {
+        let entry = self.current_entry();
+        let depth = entry.depth();
+        if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            return Ok(());
+        }
+        let mut dir = entry.open()?;
+        if dir.follow_all_symlinks().is_err() || dir.file_type() != Some(FileType::Directory) {
+            return Ok(());
+        }
+        let inode_ref = dir.inode_ref();
+        // Following a symlink to an ancestor, including the targets of other followed symlinks,
+        // would recurse forever
+        if inode_ref == self.root || self.parents.contains(&dir.inode()) {
+            return Ok(());
+        }
+
         let mut err = 0;
-        let has_next = unsafe { ffi::sqsh_tree_traversal_next(self.inner.as_ptr(), &mut err) };
+        let traversal = unsafe { ffi::sqsh_tree_traversal_new(dir.inner.as_ptr(), &mut err) };
+        let traversal = match NonNull::new(traversal) {
+            Some(traversal) => traversal,
+            None => return Err(error::new(err)),
+        };
+        if let Some(max_depth) = self.max_depth {
+            unsafe { ffi::sqsh_tree_traversal_set_max_depth(traversal.as_ptr(), max_depth - depth) }
+        }
+        // Skip the root of the new traversal: it was already visited as the symlink
+        let _ = unsafe { ffi::sqsh_tree_traversal_next(traversal.as_ptr(), &mut err) };
         if err != 0 {
+            unsafe { ffi::sqsh_tree_traversal_free(traversal.as_ptr()) };
             return Err(error::new(err));
         }
-        Ok(has_next.then_some(Entry {
-            inner: unsafe { self.inner.as_ref() },
-            _marker: PhantomData,
+
+        self.outer.push(mem::replace(&mut self.inner, traversal));
+        // The root of the new traversal was skipped, and `advance_raw` skips its exit, popping this
+        self.parents.push(dir.inode());
+        self.followed.push(dir);
+        Ok(())
+    }
+}
+
+/// An iterator over the paths and files under a path in an archive.
+///
+/// Created by [`Archive::walk_files`]. Unlike [`Traversal`], this is a regular iterator, which
+/// opens each file it yields. Directories are skipped by default, see
+/// [`WalkFiles::include_dirs`].
+pub struct WalkFiles<'archive> {
+    traversal: Traversal<'archive>,
+    include_dirs: bool,
+    done: bool,
+}
+
+impl<'archive> Archive<'archive> {
+    /// Walk the tree under `path`, yielding the path and opened file of each entry.
+    ///
+    /// Paths are relative to `path`. Symlinks are not followed, except for `path` itself.
+    pub fn walk_files(&self, path: &str) -> error::Result<WalkFiles<'_>> {
+        let traversal = self.open(path)?.into_traversal()?;
+        Ok(WalkFiles {
+            traversal,
+            include_dirs: false,
+            done: false,
+        })
+    }
+}
+
+impl WalkFiles<'_> {
+    /// Set whether directories should be yielded, in addition to all other files.
+    ///
+    /// A directory is yielded before any of its contents. The root of the walk is yielded with
+    /// an empty path if it is a directory.
+    #[must_use]
+    pub fn include_dirs(mut self, include_dirs: bool) -> Self {
+        self.include_dirs = include_dirs;
+        self
+    }
+}
+
+impl<'archive> Iterator for WalkFiles<'archive> {
+    type Item = error::Result<(BString, File<'archive>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let entry = match self.traversal.advance() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            let skip = match entry.state() {
+                State::DirectorySecond => true,
+                State::DirectoryFirst => !self.include_dirs,
+                State::Normal => entry.is_dir() && !self.include_dirs,
+            };
+            if !skip {
+                return Some(entry.open().map(|file| (entry.path_buf(), file)));
+            }
+        }
+    }
+}
+
+impl FusedIterator for WalkFiles<'_> {}
+
+/// An iterator over the entries under a path, with a running total of the size of the files.
+///
+/// Created by [`Archive::walk_with_progress`] or [`WalkFiles::with_progress`].
+pub struct WalkProgress<'archive> {
+    walk: WalkFiles<'archive>,
+    bytes_visited: u64,
+}
+
+impl<'archive> Archive<'archive> {
+    /// Walk the tree under `path`, yielding each entry along with the total size of all regular
+    /// files visited so far (including the current entry).
+    ///
+    /// This allows showing progress during a walk, e.g. for an extraction, without a separate
+    /// pass to compute sizes. Directories are included, see [`WalkFiles::include_dirs`].
+    pub fn walk_with_progress(&self, path: &str) -> error::Result<WalkProgress<'_>> {
+        Ok(self.walk_files(path)?.include_dirs(true).with_progress())
+    }
+}
+
+impl<'archive> WalkFiles<'archive> {
+    /// Yield a running total of the size of the regular files visited, with each entry.
+    #[must_use]
+    pub fn with_progress(self) -> WalkProgress<'archive> {
+        WalkProgress {
+            walk: self,
+            bytes_visited: 0,
+        }
+    }
+}
+
+impl WalkProgress<'_> {
+    /// Returns the total size of the regular files visited so far.
+    #[must_use]
+    pub fn bytes_visited(&self) -> u64 {
+        self.bytes_visited
+    }
+}
+
+impl<'archive> Iterator for WalkProgress<'archive> {
+    type Item = error::Result<(BString, File<'archive>, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.walk.next()?;
+        Some(result.map(|(path, file)| {
+            if file.file_type() == Some(FileType::File) {
+                self.bytes_visited += file.size();
+            }
+            (path, file, self.bytes_visited)
         }))
     }
 }
 
+impl FusedIterator for WalkProgress<'_> {}
+
+impl Archive<'_> {
+    /// Call `f` with the path and opened file of each entry under `path`, using `threads`
+    /// worker threads.
+    ///
+    /// The tree is walked on the calling thread, and each worker opens its own [`File`] for the
+    /// entries it's given, so `f` is called concurrently, in no particular order. Like
+    /// [`Archive::walk_files`], paths are relative to `path`, directories are skipped, and
+    /// symlinks are not followed, except for `path` itself. A `threads` of 0 is treated as 1.
+    ///
+    /// The first error encountered, either while walking or opening an entry, stops the walk and
+    /// is returned, though `f` may still be called for entries which were already opened.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the walk is stopped, and the panic is resumed on the calling thread once
+    /// the other workers have finished.
+    pub fn par_for_each_file<F>(&self, path: &str, threads: usize, f: F) -> error::Result<()>
+    where
+        F: Fn(&BStr, &File<'_>) + Sync,
+    {
+        let (sender, receiver) = mpsc::sync_channel::<(BString, InodeRef)>(threads.max(1) * 4);
+        let receiver = Mutex::new(receiver);
+        let first_error = Mutex::new(None);
+        let failed = AtomicBool::new(false);
+
+        let fail = |e: Error| {
+            failed.store(true, Ordering::Relaxed);
+            first_error.lock().unwrap().get_or_insert(e);
+        };
+        let work = || {
+            let mut guard = StopWalkOnExit {
+                failed: &failed,
+                receiver: &receiver,
+                finished: false,
+            };
+            loop {
+                // Only hold the lock while waiting for the next entry, not while calling `f`
+                let next = receiver.lock().unwrap().recv();
+                let Ok((path, inode_ref)) = next else {
+                    guard.finished = true;
+                    break;
+                };
+                match self.open_ref(inode_ref) {
+                    Ok(file) => f(path.as_ref(), &file),
+                    Err(e) => {
+                        fail(e);
+                        break;
+                    }
+                }
+            }
+        };
+        let walk = |sender: mpsc::SyncSender<_>| -> error::Result<()> {
+            let mut traversal = self.open(path)?.into_traversal()?;
+            while let Some(entry) = traversal.advance()? {
+                if failed.load(Ordering::Relaxed) {
+                    break;
+                }
+                if entry.is_dir() {
+                    continue;
+                }
+                let inode_ref = match entry.directory_entry() {
+                    Some(dir_entry) => dir_entry.inode_ref(),
+                    // The root of the walk has no directory entry
+                    None => entry.open()?.inode_ref(),
+                };
+                if sender.send((entry.path_buf(), inode_ref)).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        };
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1)).map(|_| scope.spawn(work)).collect();
+            // The sender is dropped when the walk returns, letting the workers finish
+            if let Err(e) = walk(sender) {
+                fail(e);
+            }
+            for worker in workers {
+                if let Err(panic) = worker.join() {
+                    panic::resume_unwind(panic);
+                }
+            }
+        });
+        match first_error.into_inner().unwrap() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+// Stops the walk of `Archive::par_for_each_file` if a worker exits early, because of an error or
+// a panic in the callback
+struct StopWalkOnExit<'a, T> {
+    failed: &'a AtomicBool,
+    receiver: &'a Mutex<mpsc::Receiver<T>>,
+    finished: bool,
+}
+
+impl<T> Drop for StopWalkOnExit<'_, T> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.failed.store(true, Ordering::Relaxed);
+        // If this was the last worker, the walk may be blocked sending to a full channel: make
+        // room, so it can notice the failure
+        if let Ok(receiver) = self.receiver.lock() {
+            while receiver.try_recv().is_ok() {}
+        }
+    }
+}
+
 impl Drop for Traversal<'_> {
     fn drop(&mut self) {
         unsafe {
             ffi::sqsh_tree_traversal_free(self.inner.as_ptr());
+            for outer in &self.outer {
+                ffi::sqsh_tree_traversal_free(outer.as_ptr());
+            }
         }
     }
 }
@@ -99,7 +521,12 @@ impl<'traversal, 'archive> Entry<'traversal, 'archive> {
     /// The root entry has a depth of 0.
     #[must_use]
     pub fn depth(self) -> usize {
-        unsafe { ffi::sqsh_tree_traversal_depth(self.inner) }
+        let outer_depth: usize = self
+            .outer
+            .iter()
+            .map(|outer| unsafe { ffi::sqsh_tree_traversal_depth(outer.as_ptr()) })
+            .sum();
+        outer_depth + unsafe { ffi::sqsh_tree_traversal_depth(self.inner) }
     }
 
     /// The name of this entry.
@@ -124,6 +551,24 @@ impl<'traversal, 'archive> Entry<'traversal, 'archive> {
         Path::new(self)
     }
 
+    /// The path of this entry, as an owned byte string.
+    ///
+    /// This is the same as the [path][Self::path], with its segments joined with `/`. Unlike
+    /// formatting the path with `Display`, the bytes of each segment are preserved exactly.
+    #[must_use]
+    pub fn path_buf(self) -> BString {
+        let mut result = BString::from(Vec::with_capacity(self.path().byte_len()));
+        let mut segments = self.path().segments();
+        if let Some(segment) = segments.next() {
+            result.extend_from_slice(segment);
+            for segment in segments {
+                result.push(b'/');
+                result.extend_from_slice(segment);
+            }
+        }
+        result
+    }
+
     /// Open the current entry.
     pub fn open(self) -> error::Result<File<'archive>> {
         let mut err = 0;
@@ -135,12 +580,69 @@ impl<'traversal, 'archive> Entry<'traversal, 'archive> {
         Ok(unsafe { File::new(file) })
     }
 
+    /// Open the current entry, and return a reader for its contents.
+    ///
+    /// This is a shortcut for `entry.open()?.into_reader()`.
+    pub fn reader(self) -> error::Result<Reader<'archive>> {
+        self.open()?.into_reader()
+    }
+
+    /// Read the target of the current entry, if it is a symlink.
+    ///
+    /// Returns `None` if the entry is not a symlink. The symlink itself is opened, it is never
+    /// followed.
+    pub fn symlink_target(self) -> error::Result<Option<BString>> {
+        if !self.is_symlink() {
+            return Ok(None);
+        }
+        let file = self.open()?;
+        Ok(file.symlink_path().map(BString::from))
+    }
+
     #[must_use]
     pub fn file_type(self) -> FileType {
         let file_type = unsafe { ffi::sqsh_tree_traversal_type(self.inner) };
         FileType::try_from(file_type).unwrap()
     }
 
+    /// Returns `true` if this entry is a directory.
+    #[must_use]
+    pub fn is_dir(self) -> bool {
+        self.file_type() == FileType::Directory
+    }
+
+    /// Returns `true` if this entry is a regular file.
+    #[must_use]
+    pub fn is_file(self) -> bool {
+        self.file_type() == FileType::File
+    }
+
+    /// Returns `true` if this entry is a symlink.
+    ///
+    /// Symlinks are not followed, so this is `true` for symlinks to directories even if the
+    /// traversal follows symlinks (see [`Traversal::set_follow_symlinks`]).
+    #[must_use]
+    pub fn is_symlink(self) -> bool {
+        self.file_type() == FileType::Symlink
+    }
+
+    /// The inode number of the directory containing this entry.
+    ///
+    /// This is `None` for the root of the traversal. When following symlinks, entries inside
+    /// the target of a symlink have the target directory as their parent.
+    #[must_use]
+    pub fn parent_inode(self) -> Option<Inode> {
+        self.parent
+    }
+
+    /// The character used by `ls -l` to indicate the type of this entry.
+    ///
+    /// See [`FileType::type_char`].
+    #[must_use]
+    pub fn type_char(self) -> char {
+        self.file_type().type_char()
+    }
+
     /// The directory entry for this entry. This will be present for everything but the root entry.
     #[must_use]
     pub fn directory_entry(self) -> Option<crate::directory::DirectoryEntry<'traversal, 'archive>> {
@@ -197,6 +699,17 @@ impl<'traversal> Path<'traversal> {
     pub fn segments(self) -> PathSegments<'traversal> {
         PathSegments::new(self.entry)
     }
+
+    /// The length in bytes of the path, with segments joined by `/`.
+    ///
+    /// This is the length of [`Entry::path_buf`], and can be used to reserve space before
+    /// building the path.
+    #[must_use]
+    pub fn byte_len(self) -> usize {
+        let segments = self.segments();
+        let separators = segments.len().saturating_sub(1);
+        segments.map(|segment| segment.len()).sum::<usize>() + separators
+    }
 }
 
 impl fmt::Debug for Path<'_> {
@@ -222,14 +735,27 @@ impl<'a> PathSegments<'a> {
     pub(crate) fn new(entry: Entry<'a, '_>) -> Self {
         Self {
             inner: entry.inner,
+            outer: entry.outer,
             depth: entry.depth(),
             index: 0,
         }
     }
 
-    fn segment(&self, index: usize) -> &'a BStr {
+    fn segment(&self, mut index: usize) -> &'a BStr {
+        // Segments of followed symlinks come first, then the segments inside the current target
+        let mut traversal = self.inner;
+        for outer in self.outer {
+            let outer = unsafe { outer.as_ref() };
+            let depth = unsafe { ffi::sqsh_tree_traversal_depth(outer) };
+            if index < depth {
+                traversal = outer;
+                break;
+            }
+            index -= depth;
+        }
+
         let mut len = 0;
-        let segment = unsafe { ffi::sqsh_tree_traversal_path_segment(self.inner, &mut len, index) };
+        let segment = unsafe { ffi::sqsh_tree_traversal_path_segment(traversal, &mut len, index) };
 
         debug_assert!(!segment.is_null());
         let slice = unsafe { std::slice::from_raw_parts(segment.cast::<u8>(), len) };
@@ -0,0 +1,48 @@
+//! Helpers for working with the contents of archives.
+
+use bstr::BStr;
+use std::path::{Path, PathBuf};
+
+/// Join a path from an archive onto `base`, refusing paths which could escape `base`.
+///
+/// `entry_path` is a `/` separated path, as returned by
+/// [`Entry::path_buf`](crate::traverse::Entry::path_buf). Empty and `.` segments are ignored,
+/// so an empty path returns `base` itself. Returns `None` for absolute paths, and for paths
+/// containing a `..` segment or a NUL byte.
+///
+/// Paths from untrusted archives should be passed through this before writing anything to
+/// disk, so extraction can never write outside of the destination directory. Note that this
+/// only checks the path itself: symlinks already extracted under `base` may still point
+/// elsewhere.
+#[must_use]
+pub fn sanitize_path(base: &Path, entry_path: &BStr) -> Option<PathBuf> {
+    if entry_path.first() == Some(&b'/') || entry_path.contains(&0) {
+        return None;
+    }
+    let mut path = base.to_owned();
+    for segment in entry_path.split(|&b| b == b'/') {
+        match segment {
+            b"" | b"." => {}
+            b".." => return None,
+            segment => path.push(segment_to_path(segment)?),
+        }
+    }
+    Some(path)
+}
+
+#[cfg(unix)]
+fn segment_to_path(segment: &[u8]) -> Option<&Path> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Some(Path::new(std::ffi::OsStr::from_bytes(segment)))
+}
+
+#[cfg(not(unix))]
+fn segment_to_path(segment: &[u8]) -> Option<&Path> {
+    let segment = std::str::from_utf8(segment).ok()?;
+    // Separators and drive prefixes would let a single segment escape `base`
+    if segment.contains(['\\', ':']) {
+        return None;
+    }
+    Some(Path::new(segment))
+}
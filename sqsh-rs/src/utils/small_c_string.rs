@@ -1,5 +1,4 @@
 use crate::{error, Error};
-use sqsh_sys::SqshError;
 use std::ffi::{CStr, CString};
 use std::mem::MaybeUninit;
 use std::ptr;
@@ -36,7 +35,7 @@ unsafe fn run_with_cstr_stack<T>(
     }
 
     let c = CStr::from_bytes_with_nul(unsafe { slice::from_raw_parts(buf_ptr, bytes.len() + 1) })
-        .map_err(|_| Error(SqshError::SQSH_ERROR_INVALID_ARGUMENT))?;
+        .map_err(|_| Error::InteriorNul)?;
     f(c)
 }
 
@@ -48,6 +47,6 @@ fn run_with_cstr_allocating<T>(
 ) -> error::Result<T> {
     match CString::new(bytes) {
         Ok(s) => f(&s),
-        Err(_) => Err(Error(SqshError::SQSH_ERROR_INVALID_ARGUMENT)),
+        Err(_) => Err(Error::InteriorNul),
     }
 }
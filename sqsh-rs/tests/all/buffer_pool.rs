@@ -0,0 +1,41 @@
+use sqsh_rs::BufferPool;
+
+#[test]
+fn buffers_are_reused() {
+    let archive = crate::archive();
+    let pool = BufferPool::for_archive(&archive);
+    assert_eq!(pool.buffer_size(), 131072);
+    assert_eq!(pool.idle_count(), 0);
+
+    let file = archive.open("short.file").unwrap();
+    let mut buf = pool.get();
+    assert!(buf.capacity() >= pool.buffer_size());
+    file.read_into(&mut buf).unwrap();
+    assert_eq!(&buf[..], b"abc\n");
+    let ptr = buf.as_ptr();
+    drop(buf);
+    assert_eq!(pool.idle_count(), 1);
+
+    let buf = pool.get();
+    assert!(buf.is_empty());
+    assert_eq!(buf.as_ptr(), ptr);
+    assert_eq!(pool.idle_count(), 0);
+}
+
+#[test]
+fn shared_between_threads() {
+    let archive = crate::archive();
+    let pool = BufferPool::for_archive(&archive);
+    std::thread::scope(|s| {
+        for path in ["1MiB.file", "short.file", "one.file"] {
+            let (archive, pool) = (&archive, &pool);
+            s.spawn(move || {
+                let file = archive.open(path).unwrap();
+                let mut buf = pool.get();
+                let len = file.read_into(&mut buf).unwrap();
+                assert_eq!(len as u64, file.size());
+            });
+        }
+    });
+    assert!(pool.idle_count() >= 1);
+}
@@ -0,0 +1,52 @@
+use super::image::{directory_inode, file_inode, listing, Image, FILE, LZMA};
+use sqsh_rs::superblock::CompressionOptions;
+use sqsh_rs::{Archive, Compression};
+
+const CONTENTS: &[u8] = b"abcabcabcabc";
+
+// `CONTENTS`, compressed in the lzma alone format with a 128KiB dictionary
+const COMPRESSED: [u8; 28] = [
+    0x5d, 0x00, 0x00, 0x02, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x30, 0x98,
+    0x88, 0xa7, 0xeb, 0x62, 0x81, 0xa3, 0xff, 0xff, 0x61, 0x64, 0x00, 0x00,
+];
+
+// The crate has no layout for lzma options, so they are opaque
+const OPTIONS: [u8; 8] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+/// An lzma image with a compression options block, containing a single file `file`.
+fn lzma_image() -> Vec<u8> {
+    let data_start = Image::data_start(Some(&OPTIONS));
+    let size = u32::try_from(CONTENTS.len()).unwrap();
+    let compressed_size = u32::try_from(COMPRESSED.len()).unwrap();
+    let mut inodes = file_inode(1, data_start, size, Some(compressed_size));
+
+    let root_listing = listing(&[("file", FILE, 1, 0)]);
+    let root_offset = u16::try_from(inodes.len()).unwrap();
+    inodes.extend(directory_inode(2, 2, 0, root_listing.len(), 3));
+
+    Image {
+        compression: LZMA,
+        compression_options: Some(&OPTIONS),
+        data: &COMPRESSED,
+        inodes: &inodes,
+        directories: &root_listing,
+        inode_count: 2,
+        root_offset,
+    }
+    .build()
+}
+
+#[test]
+fn unknown_compression_options() {
+    let archive = Archive::from_vec(lzma_image()).unwrap();
+    assert_eq!(archive.compression_type(), Compression::LZMA);
+    assert_eq!(
+        archive.compression_options().unwrap(),
+        Some(CompressionOptions::Unknown {
+            compression: Compression::LZMA
+        })
+    );
+    // Only reading data needs a decompressor
+    #[cfg(feature = "lzma")]
+    assert_eq!(archive.read("file").unwrap(), CONTENTS);
+}
@@ -0,0 +1,60 @@
+use super::image::{
+    directory_inode, file_inode, listing, symlink_inode, Image, DIRECTORY, FILE, GZIP, SYMLINK,
+};
+use sqsh_rs::{Archive, ExtractErrorKind};
+use std::path::Path;
+
+/// An image which mksquashfs can't create: the root contains a symlink `a -> ..`, followed by
+/// a directory which is also named `a`, containing an empty file `escaped`.
+fn duplicate_name_image() -> Vec<u8> {
+    let mut inodes = symlink_inode(1, "..");
+
+    let file_offset = u16::try_from(inodes.len()).unwrap();
+    inodes.extend(file_inode(2, 0, 0, None));
+
+    let dir_listing = listing(&[("escaped", FILE, 2, file_offset)]);
+    let dir_offset = u16::try_from(inodes.len()).unwrap();
+    inodes.extend(directory_inode(3, 2, 0, dir_listing.len(), 4));
+
+    let root_listing = listing(&[("a", SYMLINK, 1, 0), ("a", DIRECTORY, 3, dir_offset)]);
+    let root_offset = u16::try_from(inodes.len()).unwrap();
+    let root_listing_offset = u16::try_from(dir_listing.len()).unwrap();
+    inodes.extend(directory_inode(
+        4,
+        3,
+        root_listing_offset,
+        root_listing.len(),
+        5,
+    ));
+
+    Image {
+        compression: GZIP,
+        compression_options: None,
+        data: &[],
+        inodes: &inodes,
+        directories: &[dir_listing, root_listing].concat(),
+        inode_count: 4,
+        root_offset,
+    }
+    .build()
+}
+
+#[test]
+fn extract_duplicate_symlink_and_directory() {
+    let image = duplicate_name_image();
+    let archive = Archive::from_slice(&image).unwrap();
+    let base = Path::new(env!("CARGO_TARGET_TMPDIR")).join("extract_duplicate_names");
+    let _ = std::fs::remove_dir_all(&base);
+    let dest = base.join("dest");
+
+    // The symlink `a -> ..` is extracted first, then writing `a/escaped` would follow it
+    let err = archive.extract_to_verified("", &dest, |_| {}).unwrap_err();
+    assert_eq!(err.path(), "a");
+    assert!(matches!(err.kind(), ExtractErrorKind::Io(_)));
+    assert!(!base.join("escaped").exists());
+    assert!(std::fs::symlink_metadata(dest.join("a"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    std::fs::remove_dir_all(&base).unwrap();
+}
@@ -0,0 +1,51 @@
+use sqsh_rs::FileType;
+
+const ALL: [FileType; 7] = [
+    FileType::Directory,
+    FileType::File,
+    FileType::Symlink,
+    FileType::BlockDevice,
+    FileType::CharacterDevice,
+    FileType::Socket,
+    FileType::Fifo,
+];
+
+#[test]
+fn mode_round_trip() {
+    for file_type in ALL {
+        let mode = file_type.to_mode();
+        assert_eq!(FileType::from_mode(mode), Some(file_type));
+        // Permission bits are ignored
+        assert_eq!(FileType::from_mode(mode | 0o7777), Some(file_type));
+    }
+}
+
+#[test]
+fn from_mode() {
+    assert_eq!(FileType::from_mode(0o040_755), Some(FileType::Directory));
+    assert_eq!(FileType::from_mode(0o100_644), Some(FileType::File));
+    assert_eq!(FileType::from_mode(0o120_777), Some(FileType::Symlink));
+    assert_eq!(FileType::from_mode(0o644), None);
+}
+
+#[test]
+fn archive_file_mode() {
+    let archive = crate::archive();
+    let file = archive.open_nofollow("short.link").unwrap();
+    let file_type = file.file_type().unwrap();
+    assert_eq!(
+        FileType::from_mode(file_type.to_mode()),
+        Some(FileType::Symlink)
+    );
+}
+
+#[test]
+fn type_char() {
+    assert_eq!(FileType::Directory.type_char(), 'd');
+    assert_eq!(FileType::File.type_char(), '-');
+    assert_eq!(FileType::Symlink.type_char(), 'l');
+    assert_eq!(FileType::BlockDevice.type_char(), 'b');
+    assert_eq!(FileType::CharacterDevice.type_char(), 'c');
+    assert_eq!(FileType::Fifo.type_char(), 'p');
+    assert_eq!(FileType::Socket.type_char(), 's');
+}
@@ -0,0 +1,177 @@
+//! Builds small images by hand, for layouts which mksquashfs can't or won't create.
+//!
+//! Every metadata block is stored uncompressed, so images can be built without a compressor,
+//! whatever compression the superblock records.
+
+const SUPERBLOCK_SIZE: u64 = 96;
+const UNCOMPRESSED_METADATA: u16 = 0x8000;
+const UNCOMPRESSED_DATA: u32 = 1 << 24;
+const NO_TABLE: u64 = u64::MAX;
+
+pub const DIRECTORY: u16 = 1;
+pub const FILE: u16 = 2;
+pub const SYMLINK: u16 = 3;
+
+pub const GZIP: u16 = 1;
+pub const LZMA: u16 = 2;
+
+pub struct Image<'a> {
+    pub compression: u16,
+    pub compression_options: Option<&'a [u8]>,
+    /// Data blocks, already compressed, which start at [`Image::data_start`].
+    pub data: &'a [u8],
+    /// The uncompressed inode table, which must fit in a single metadata block.
+    pub inodes: &'a [u8],
+    /// The uncompressed directory table, which must fit in a single metadata block.
+    pub directories: &'a [u8],
+    pub inode_count: u32,
+    /// The offset of the root directory inode in `inodes`.
+    pub root_offset: u16,
+}
+
+impl Image<'_> {
+    /// The offset of the data blocks in the image.
+    pub fn data_start(compression_options: Option<&[u8]>) -> u64 {
+        SUPERBLOCK_SIZE + compression_options.map_or(0, |options| options.len() as u64 + 2)
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let inode_table = metadata_block(self.inodes);
+        let directory_table = metadata_block(self.directories);
+        // A single id, 0
+        let id_block = metadata_block(&0_u32.to_le_bytes());
+
+        let inode_table_start = Self::data_start(self.compression_options) + self.data.len() as u64;
+        let directory_table_start = inode_table_start + inode_table.len() as u64;
+        let id_block_start = directory_table_start + directory_table.len() as u64;
+        let id_table_start = id_block_start + id_block.len() as u64;
+        let bytes_used = id_table_start + 8;
+
+        // Uncompressed inodes, data, fragments and ids, no fragments, and no xattrs
+        let mut flags = 0x0a1b_u16;
+        if self.compression_options.is_some() {
+            flags |= 0x0400;
+        }
+
+        let mut image = Vec::new();
+        image.extend_from_slice(&0x7371_7368_u32.to_le_bytes());
+        image.extend_from_slice(&self.inode_count.to_le_bytes());
+        // Modification time, block size, and fragment count
+        image.extend_from_slice(&0_u32.to_le_bytes());
+        image.extend_from_slice(&131_072_u32.to_le_bytes());
+        image.extend_from_slice(&0_u32.to_le_bytes());
+        image.extend_from_slice(&self.compression.to_le_bytes());
+        // The log2 of the block size
+        image.extend_from_slice(&17_u16.to_le_bytes());
+        image.extend_from_slice(&flags.to_le_bytes());
+        // Id count and version
+        image.extend_from_slice(&1_u16.to_le_bytes());
+        image.extend_from_slice(&4_u16.to_le_bytes());
+        image.extend_from_slice(&0_u16.to_le_bytes());
+        image.extend_from_slice(&u64::from(self.root_offset).to_le_bytes());
+        image.extend_from_slice(&bytes_used.to_le_bytes());
+        image.extend_from_slice(&id_table_start.to_le_bytes());
+        // Xattr id table
+        image.extend_from_slice(&NO_TABLE.to_le_bytes());
+        image.extend_from_slice(&inode_table_start.to_le_bytes());
+        image.extend_from_slice(&directory_table_start.to_le_bytes());
+        // Fragment and export tables
+        image.extend_from_slice(&NO_TABLE.to_le_bytes());
+        image.extend_from_slice(&NO_TABLE.to_le_bytes());
+        assert_eq!(image.len() as u64, SUPERBLOCK_SIZE);
+
+        if let Some(options) = self.compression_options {
+            image.extend(metadata_block(options));
+        }
+        image.extend_from_slice(self.data);
+        image.extend(inode_table);
+        image.extend(directory_table);
+        image.extend(id_block);
+        image.extend_from_slice(&id_block_start.to_le_bytes());
+        image.resize(image.len().next_multiple_of(4096), 0);
+        image
+    }
+}
+
+fn metadata_block(data: &[u8]) -> Vec<u8> {
+    let header = u16::try_from(data.len()).unwrap() | UNCOMPRESSED_METADATA;
+    let mut block = header.to_le_bytes().to_vec();
+    block.extend_from_slice(data);
+    block
+}
+
+fn inode_header(inode_type: u16, mode: u16, number: u32) -> Vec<u8> {
+    let mut inode = Vec::new();
+    inode.extend_from_slice(&inode_type.to_le_bytes());
+    inode.extend_from_slice(&mode.to_le_bytes());
+    // uid and gid indexes, and the modification time
+    inode.extend_from_slice(&[0; 8]);
+    inode.extend_from_slice(&number.to_le_bytes());
+    inode
+}
+
+pub fn directory_inode(
+    number: u32,
+    link_count: u32,
+    listing_offset: u16,
+    listing_len: usize,
+    parent: u32,
+) -> Vec<u8> {
+    let mut inode = inode_header(DIRECTORY, 0o755, number);
+    // Start block of the listing
+    inode.extend_from_slice(&0_u32.to_le_bytes());
+    inode.extend_from_slice(&link_count.to_le_bytes());
+    let size = u16::try_from(listing_len + 3).unwrap();
+    inode.extend_from_slice(&size.to_le_bytes());
+    inode.extend_from_slice(&listing_offset.to_le_bytes());
+    inode.extend_from_slice(&parent.to_le_bytes());
+    inode
+}
+
+/// A file stored in a single data block at `data_start`, or with no blocks if it's empty. The
+/// block is stored uncompressed, unless its `compressed_size` is given.
+pub fn file_inode(
+    number: u32,
+    data_start: u64,
+    size: u32,
+    compressed_size: Option<u32>,
+) -> Vec<u8> {
+    let mut inode = inode_header(FILE, 0o644, number);
+    inode.extend_from_slice(&u32::try_from(data_start).unwrap().to_le_bytes());
+    // No fragment
+    inode.extend_from_slice(&u32::MAX.to_le_bytes());
+    inode.extend_from_slice(&0_u32.to_le_bytes());
+    inode.extend_from_slice(&size.to_le_bytes());
+    let block = compressed_size.unwrap_or(size | UNCOMPRESSED_DATA);
+    if size != 0 {
+        inode.extend_from_slice(&block.to_le_bytes());
+    }
+    inode
+}
+
+pub fn symlink_inode(number: u32, target: &str) -> Vec<u8> {
+    let mut inode = inode_header(SYMLINK, 0o777, number);
+    inode.extend_from_slice(&1_u32.to_le_bytes());
+    inode.extend_from_slice(&u32::try_from(target.len()).unwrap().to_le_bytes());
+    inode.extend_from_slice(target.as_bytes());
+    inode
+}
+
+/// A directory listing. Entries are (name, inode type, inode number, offset of the inode in the
+/// inode table).
+pub fn listing(entries: &[(&str, u16, u32, u16)]) -> Vec<u8> {
+    let base = entries[0].2;
+    let mut listing = Vec::new();
+    listing.extend_from_slice(&u32::try_from(entries.len() - 1).unwrap().to_le_bytes());
+    listing.extend_from_slice(&0_u32.to_le_bytes());
+    listing.extend_from_slice(&base.to_le_bytes());
+    for &(name, inode_type, number, offset) in entries {
+        let delta = i16::try_from(number - base).unwrap();
+        listing.extend_from_slice(&offset.to_le_bytes());
+        listing.extend_from_slice(&delta.to_le_bytes());
+        listing.extend_from_slice(&inode_type.to_le_bytes());
+        listing.extend_from_slice(&u16::try_from(name.len() - 1).unwrap().to_le_bytes());
+        listing.extend_from_slice(name.as_bytes());
+    }
+    listing
+}
@@ -31,3 +31,31 @@ fn inode_map_fails_on_invalid_inode() {
     let result = inode_map.get(Inode::new(u32::MAX).unwrap());
     assert!(result.is_err());
 }
+
+#[test]
+fn inode_map_set() {
+    let archive = archive();
+    let inode_map = archive.inode_map().unwrap();
+    let inode = Inode::new(1).unwrap();
+    let inode_ref = inode_map.get(inode).unwrap();
+    // Setting a consistent value is fine
+    inode_map.set(inode, inode_ref).unwrap();
+    assert_eq!(inode_map.get(inode).unwrap(), inode_ref);
+}
+
+#[test]
+fn resolve_inode() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    while let Some(entry) = traversal.advance().unwrap() {
+        let file = entry.open().unwrap();
+        assert_eq!(
+            archive.resolve_inode(file.inode()).unwrap(),
+            file.inode_ref()
+        );
+    }
+
+    let result = archive.resolve_inode(Inode::new(u32::MAX).unwrap());
+    assert!(result.is_err());
+}
@@ -1,3 +1,10 @@
+mod buffer_pool;
+mod compression_options;
 mod custom_source;
+mod extract;
+mod file_type;
+mod image;
 mod inode_map;
 mod path_resolver;
+mod permissions;
+mod sanitize_path;
@@ -51,3 +51,82 @@ fn navigation() {
     // Only two items
     assert!(!resolver.advance().unwrap());
 }
+
+#[test]
+fn lookup_case_insensitive() {
+    let archive = crate::archive();
+    let mut resolver = archive.path_resolver().unwrap();
+
+    resolver.advance_lookup_ci(b"SUBDIR").unwrap();
+    assert_eq!(resolver.current_name(), Some(BStr::new("subdir")));
+    assert_eq!(resolver.current_file_type(), Some(FileType::Directory));
+
+    resolver.down().unwrap();
+    resolver.advance_lookup_ci(b"Short.File").unwrap();
+    assert_eq!(resolver.current_name(), Some(BStr::new("short.file")));
+
+    // Looking up an earlier entry after a later one restarts from the beginning
+    resolver.advance_lookup_ci(b"ONE.FILE").unwrap();
+    assert_eq!(resolver.current_name(), Some(BStr::new("one.file")));
+
+    let err = resolver.advance_lookup_ci(b"not_exists").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn realpath() {
+    let archive = crate::archive();
+
+    assert_eq!(archive.realpath("").unwrap(), "/");
+    assert_eq!(archive.realpath("/").unwrap(), "/");
+    assert_eq!(archive.realpath("..").unwrap(), "/");
+    assert_eq!(archive.realpath("one.file").unwrap(), "/one.file");
+    assert_eq!(archive.realpath("short.link").unwrap(), "/short.file");
+    assert_eq!(
+        archive.realpath("./subdir//../subdir/./one.file").unwrap(),
+        "/subdir/one.file"
+    );
+    assert_eq!(
+        archive.realpath("deep/level1/level2/").unwrap(),
+        "/deep/level1/level2"
+    );
+}
+
+#[test]
+fn realpath_errors() {
+    let archive = crate::archive();
+
+    let err = archive.realpath("not_exists").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+    let err = archive.realpath("broken.link").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+    let err = archive.realpath("subdir/not_exists/one.file").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+
+    assert!(archive.realpath("one.file/one.file").is_err());
+}
+
+#[test]
+fn lookup_path() {
+    let archive = crate::archive();
+    let mut resolver = archive.path_resolver().unwrap();
+    resolver
+        .advance_lookup_path(b"deep/level1//level2/level3")
+        .unwrap();
+    assert_eq!(resolver.current_name(), Some(BStr::new("level3")));
+    assert_eq!(
+        resolver.open().unwrap().inode_ref(),
+        archive
+            .open("deep/level1/level2/level3")
+            .unwrap()
+            .inode_ref()
+    );
+
+    let mut resolver = archive.path_resolver().unwrap();
+    let err = resolver.advance_lookup_path(b"subdir/missing").unwrap_err();
+    assert_eq!(err.name(), "NoSuchFile");
+
+    // Symlinks are not followed
+    let mut resolver = archive.path_resolver().unwrap();
+    assert!(resolver.advance_lookup_path(b"short.link/x").is_err());
+}
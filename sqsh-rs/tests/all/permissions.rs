@@ -0,0 +1,73 @@
+use sqsh_rs::Permissions;
+
+#[test]
+fn from_octal_str() {
+    assert_eq!(
+        Permissions::from_octal_str("644").unwrap(),
+        Permissions::UserRW | Permissions::GroupRead | Permissions::OtherRead
+    );
+    assert_eq!(
+        Permissions::from_octal_str("0755").unwrap(),
+        Permissions::UserRWX
+            | Permissions::GroupRead
+            | Permissions::GroupExec
+            | Permissions::OtherRead
+            | Permissions::OtherExec
+    );
+    assert_eq!(
+        Permissions::from_octal_str("7000").unwrap(),
+        Permissions::SetUID | Permissions::SetGID | Permissions::Sticky
+    );
+    assert_eq!(
+        Permissions::from_octal_str("0").unwrap(),
+        Permissions::empty()
+    );
+}
+
+#[test]
+fn from_octal_str_invalid() {
+    for s in [
+        "",
+        "+644",
+        "-644",
+        "0o644",
+        "648",
+        "rw-r--r--",
+        "10000",
+        "77777777777",
+    ] {
+        assert!(Permissions::from_octal_str(s).is_err(), "{s:?} should fail");
+    }
+}
+
+#[test]
+fn from_mode_masks_file_type() {
+    // S_IFREG | 0644
+    assert_eq!(
+        Permissions::from_mode(0o100_644),
+        Permissions::from_octal_str("644").unwrap()
+    );
+    // S_IFDIR | 01777
+    assert_eq!(
+        Permissions::from_mode(0o041_777).to_str().as_str(),
+        "rwxrwxrwt"
+    );
+}
+
+#[test]
+fn to_octal() {
+    let permissions = Permissions::UserRW | Permissions::GroupRead | Permissions::OtherRead;
+    assert_eq!(permissions.to_octal(), 0o644);
+    assert_eq!(permissions.to_octal_string(), "0644");
+
+    let permissions = Permissions::SetUID | Permissions::from_octal_str("755").unwrap();
+    assert_eq!(permissions.to_octal(), 0o4755);
+    assert_eq!(permissions.to_octal_string(), "4755");
+
+    assert_eq!(Permissions::empty().to_octal_string(), "0000");
+    assert_eq!(Permissions::from_bits_retain(0o170644).to_octal(), 0o644);
+
+    for s in ["0000", "0644", "1777", "7777"] {
+        assert_eq!(Permissions::from_octal_str(s).unwrap().to_octal_string(), s);
+    }
+}
@@ -0,0 +1,31 @@
+use bstr::BStr;
+use sqsh_rs::util::sanitize_path;
+use std::path::Path;
+
+#[test]
+fn safe_paths() {
+    let base = Path::new("/tmp/dest");
+    assert_eq!(sanitize_path(base, BStr::new("")).unwrap(), base);
+    assert_eq!(
+        sanitize_path(base, BStr::new("subdir/one.file")).unwrap(),
+        base.join("subdir").join("one.file")
+    );
+    assert_eq!(
+        sanitize_path(base, BStr::new("./a//b/")).unwrap(),
+        base.join("a").join("b")
+    );
+    assert_eq!(
+        sanitize_path(base, BStr::new("weird/...")).unwrap(),
+        base.join("weird").join("...")
+    );
+}
+
+#[test]
+fn unsafe_paths() {
+    let base = Path::new("/tmp/dest");
+    assert_eq!(sanitize_path(base, BStr::new("/etc/passwd")), None);
+    assert_eq!(sanitize_path(base, BStr::new("..")), None);
+    assert_eq!(sanitize_path(base, BStr::new("a/../../b")), None);
+    assert_eq!(sanitize_path(base, BStr::new("a/..")), None);
+    assert_eq!(sanitize_path(base, BStr::new("a\0b")), None);
+}
@@ -1,6 +1,7 @@
 use bstr::BString;
-use sqsh_rs::traverse::Traversal;
-use sqsh_rs::{Archive, DirectoryIterator, FileType, Permissions};
+use sqsh_rs::superblock::CompressionOptions;
+use sqsh_rs::traverse::{State, Traversal};
+use sqsh_rs::{Archive, Compression, DirectoryIterator, FileType, Permissions};
 use std::fmt::Write;
 use std::io::{BufRead, Read};
 
@@ -23,6 +24,172 @@ fn mem_open_archive() {
     let _archive = Archive::from_slice(&data).unwrap();
 }
 
+#[test]
+fn builder_open_archive() {
+    let archive = Archive::builder()
+        .max_symlink_depth(1)
+        .mapper_lru_size(4)
+        .compression_lru_size(0)
+        .open(ARCHIVE_PATH)
+        .unwrap();
+    let mut file = archive.open_nofollow("short.link").unwrap();
+    file.follow_all_symlinks().unwrap();
+    assert_eq!(file.file_type(), Some(FileType::File));
+    assert_eq!(archive.read("short.link").unwrap(), b"abc\n");
+
+    let mut data = vec![0; 4096];
+    data.extend(std::fs::read(ARCHIVE_PATH).unwrap());
+    let archive = Archive::builder().offset(4096).open_slice(&data).unwrap();
+    assert_eq!(archive.read("short.file").unwrap(), b"abc\n");
+}
+
+#[test]
+fn open_with_offset_from_end() {
+    let image = std::fs::read(ARCHIVE_PATH).unwrap();
+    let mut data = vec![0; 4096];
+    data.extend_from_slice(&image);
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("appended.sqsh");
+    std::fs::write(&path, &data).unwrap();
+
+    let back_offset = u64::try_from(image.len()).unwrap();
+    let archive = Archive::new_with_offset_from_end(&path, back_offset).unwrap();
+    assert_eq!(archive.read("short.file").unwrap(), b"abc\n");
+
+    let err = Archive::new_with_offset_from_end(&path, back_offset + 4097).unwrap_err();
+    assert_eq!(err.name(), "OutOfBounds");
+}
+
+#[test]
+fn builder_nofollow_symlinks() {
+    let archive = Archive::builder()
+        .follow_symlinks(false)
+        .open(ARCHIVE_PATH)
+        .unwrap();
+    let file = archive.open("short.link").unwrap();
+    assert_eq!(file.file_type(), Some(FileType::Symlink));
+    assert!(!archive.is_file("short.link"));
+    assert_eq!(
+        archive.path_to_ref("short.link").unwrap(),
+        archive.open_nofollow("short.link").unwrap().inode_ref()
+    );
+    // Symlinks can still be followed explicitly
+    assert_eq!(
+        archive.symlink_target("short.link").unwrap().unwrap(),
+        "short.file"
+    );
+    assert_eq!(archive.read("short.file").unwrap(), b"abc\n");
+}
+
+#[test]
+fn builder_require_version() {
+    let archive = Archive::builder()
+        .require_version(4, 0)
+        .open(ARCHIVE_PATH)
+        .unwrap();
+    assert_eq!(archive.superblock().version_major(), 4);
+
+    let err = Archive::builder()
+        .require_version(5, 0)
+        .open(ARCHIVE_PATH)
+        .err()
+        .unwrap();
+    assert_eq!(err.name(), "UnsupportedVersion");
+}
+
+#[test]
+fn source_size() {
+    let file_len = std::fs::metadata(ARCHIVE_PATH).unwrap().len();
+    let archive = archive();
+    assert_eq!(archive.source_size(), file_len);
+    assert!(archive.bytes_used() <= archive.source_size());
+
+    let mut data = std::fs::read(ARCHIVE_PATH).unwrap();
+    data.resize(data.len() + 4096, 0);
+    let archive = Archive::from_slice(&data).unwrap();
+    assert_eq!(archive.source_size(), file_len + 4096);
+
+    let archive = Archive::from_vec(data).unwrap();
+    assert_eq!(archive.source_size(), file_len + 4096);
+}
+
+#[test]
+fn open_archive_from_fd() {
+    let file = std::fs::File::open(ARCHIVE_PATH).unwrap();
+    let archive = Archive::from_fd(file).unwrap();
+    assert_eq!(archive.read("short.file").unwrap(), b"abc\n");
+    assert_eq!(archive.read("1MiB.file").unwrap(), vec![b'A'; 1024 * 1024]);
+
+    let file = std::fs::File::open(ARCHIVE_PATH).unwrap();
+    let fd = std::os::fd::IntoRawFd::into_raw_fd(file);
+    let archive = unsafe { Archive::from_raw_fd(fd) }.unwrap();
+    assert_eq!(archive.read("one.file").unwrap(), b"a");
+}
+
+#[test]
+fn too_many_symlinks_error_kind() {
+    let err = sqsh_rs::Error::Sqsh(sqsh_rs::ffi::SqshError::SQSH_ERROR_TOO_MANY_SYMLINKS_FOLLOWED);
+    assert_eq!(
+        err.io_error_kind(),
+        std::io::Error::from_raw_os_error(libc::ELOOP).kind()
+    );
+}
+
+#[test]
+fn max_symlink_depth_exceeded() {
+    let archive = Archive::builder()
+        .max_symlink_depth(1)
+        .open(ARCHIVE_PATH)
+        .unwrap();
+    // chain1 -> chain2 -> chain3 -> ../short.file
+    let mut file = archive.open_nofollow("zlinks/chain1").unwrap();
+    let err = file.follow_all_symlinks().unwrap_err();
+    assert_eq!(err.name(), "TooManySymlinksFollowed");
+    assert_eq!(
+        err.io_error_kind(),
+        std::io::Error::from_raw_os_error(libc::ELOOP).kind()
+    );
+
+    let archive = Archive::new(ARCHIVE_PATH).unwrap();
+    assert_eq!(archive.read("zlinks/chain1").unwrap(), b"abc\n");
+}
+
+#[test]
+fn owned_open_archive() {
+    let data = std::fs::read(ARCHIVE_PATH).unwrap();
+    let archive = Archive::from_vec(data).unwrap();
+    assert_eq!(archive.read("short.file").unwrap(), b"abc\n");
+}
+
+#[test]
+fn clone_archive() {
+    let archive = archive();
+    let clone = archive.clone();
+    drop(archive);
+    assert_eq!(clone.read("short.file").unwrap(), b"abc\n");
+
+    let handle = clone.clone();
+    let contents = std::thread::spawn(move || handle.read("one.file").unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(contents, b"a");
+    assert_eq!(
+        clone.total_uncompressed_size().unwrap(),
+        clone.clone().total_uncompressed_size().unwrap()
+    );
+}
+
+#[test]
+fn shared_bytes_open_archive() {
+    let data: std::sync::Arc<[u8]> = std::fs::read(ARCHIVE_PATH).unwrap().into();
+    let first = Archive::from_shared_bytes(std::sync::Arc::clone(&data)).unwrap();
+    let second = Archive::from_shared_bytes(std::sync::Arc::clone(&data)).unwrap();
+    assert_eq!(std::sync::Arc::strong_count(&data), 3);
+    assert_eq!(first.read("short.file").unwrap(), b"abc\n");
+    assert_eq!(second.read("one.file").unwrap(), b"a");
+    drop((first, second));
+    assert_eq!(std::sync::Arc::strong_count(&data), 1);
+}
+
 #[test]
 fn superblock() {
     let archive = archive();
@@ -30,6 +197,99 @@ fn superblock() {
     insta::assert_debug_snapshot!(superblock);
 }
 
+#[test]
+fn archive_info() {
+    let archive = archive();
+    let info = archive.info();
+    assert_eq!(info.compression, sqsh_rs::Compression::GZIP);
+    assert_eq!(info.version_major, 4);
+    assert_eq!(info.version_minor, 0);
+    assert_eq!(info.block_size, 131072);
+    assert_eq!(info.modification_time, 2000);
+    assert_eq!(info.bytes_used, 2378);
+    assert_eq!(info.inode_count, 35);
+    assert_eq!(info.id_count, 6);
+    assert_eq!(info.fragment_entry_count, 1);
+    assert!(info.has_fragments);
+    assert!(info.has_export_table);
+    assert!(info.has_xattr_table);
+    assert!(info.has_compression_options);
+}
+
+#[test]
+fn archive_superblock_shortcuts() {
+    let archive = archive();
+    let superblock = archive.superblock();
+    assert_eq!(archive.block_size(), superblock.block_size());
+    assert_eq!(archive.compression_type(), superblock.compression_type());
+    assert_eq!(archive.inode_count(), superblock.inode_count());
+    assert_eq!(archive.modification_time(), superblock.modification_time());
+    assert_eq!(archive.bytes_used(), superblock.bytes_used());
+    assert!(archive.has_export_table());
+    assert!(archive.export_table().is_ok());
+    assert!(archive.has_xattr_table());
+}
+
+#[test]
+fn compression_id() {
+    let archive = archive();
+    let compression = archive.superblock().compression_type();
+    assert_eq!(compression, sqsh_rs::Compression::GZIP);
+    assert_eq!(compression.id(), 1);
+    assert_eq!(sqsh_rs::Compression::ZSTD.id(), 6);
+}
+
+#[test]
+fn superblock_summary() {
+    let archive = archive();
+    let superblock = archive.superblock();
+    assert_eq!(1u32 << superblock.block_log(), superblock.block_size());
+    insta::assert_snapshot!(superblock.summary(), @r###"
+    Found a valid SQUASHFS 4:0 superblock.
+    Creation or last append time 2000
+    Filesystem size 2378 bytes (2.32 Kbytes / 0.00 Mbytes)
+    Compression gzip
+    Block size 131072
+    Filesystem is exportable via NFS
+    Compressor options are present
+    Number of fragments 1
+    Number of inodes 35
+    Number of ids 6
+    "###);
+}
+
+#[test]
+fn superblock_inodes() {
+    let archive = archive();
+    let superblock = archive.superblock();
+    let inodes: Vec<_> = superblock.inodes().collect();
+    assert_eq!(inodes.len(), 35);
+    assert_eq!(inodes.first().unwrap().index(), 1);
+    assert_eq!(inodes.last().unwrap().index(), 35);
+
+    let export_table = archive.export_table().unwrap();
+    for inode in superblock.inodes() {
+        export_table.resolve_inode(inode).unwrap();
+    }
+}
+
+#[test]
+fn export_table_iter() {
+    let archive = archive();
+    let export_table = archive.export_table().unwrap();
+    assert_eq!(
+        export_table.len(),
+        archive.superblock().inode_count() as usize
+    );
+
+    let entries: Vec<_> = export_table.iter().collect();
+    assert_eq!(entries.len(), export_table.len());
+    for (inode, inode_ref) in entries {
+        assert_eq!(export_table.resolve_inode(inode).unwrap(), inode_ref);
+        assert_eq!(archive.open_ref(inode_ref).unwrap().inode(), inode);
+    }
+}
+
 #[test]
 fn easy_contents_not_exists() {
     let archive = archive();
@@ -38,6 +298,48 @@ fn easy_contents_not_exists() {
     insta::assert_snapshot!(err, @"No such file or directory");
 }
 
+#[test]
+fn open_interior_nul() {
+    let archive = archive();
+    let err = archive.open("one\0.file").err().unwrap();
+    assert_eq!(err, sqsh_rs::Error::InteriorNul);
+    assert_eq!(err.sqsh_error(), None);
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::InvalidInput);
+    insta::assert_snapshot!(err, @"Interior NUL byte");
+
+    // Long paths are converted on the heap rather than the stack
+    let long_path = format!("{}\0", "a/".repeat(1000));
+    let err = archive.open(&long_path).err().unwrap();
+    assert_eq!(err, sqsh_rs::Error::InteriorNul);
+
+    let err = archive.open("not_exists").err().unwrap();
+    assert_eq!(
+        err.sqsh_error(),
+        Some(sqsh_rs::ffi::SqshError::SQSH_ERROR_NO_SUCH_FILE)
+    );
+}
+
+#[test]
+fn error_name() {
+    use sqsh_rs::ffi::SqshError;
+    use sqsh_rs::Error;
+
+    assert_eq!(
+        Error::Sqsh(SqshError::SQSH_ERROR_NO_SUCH_FILE).name(),
+        "NoSuchFile"
+    );
+    assert_eq!(
+        Error::Sqsh(SqshError::SQSH_ERROR_NOT_A_DIRECTORY).name(),
+        "NotADirectory"
+    );
+    assert_eq!(Error::Sqsh(SqshError(libc::ENOENT as _)).name(), "Os");
+    assert_eq!(Error::InteriorNul.name(), "InteriorNul");
+
+    let archive = archive();
+    let err = archive.open("not_exists").err().unwrap();
+    assert_eq!(err.name(), "NoSuchFile");
+}
+
 #[test]
 fn easy_contents_empty() {
     let archive = archive();
@@ -45,6 +347,166 @@ fn easy_contents_empty() {
     assert!(data.is_empty());
 }
 
+#[test]
+fn reader_empty_reads() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    assert_eq!(reader.read(&mut []).unwrap(), 0);
+    assert_eq!(reader.position(), 0);
+    reader.skip(0).unwrap();
+    assert_eq!(reader.position(), 0);
+
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ab");
+    assert_eq!(reader.read(&mut []).unwrap(), 0);
+    reader.skip(0).unwrap();
+    assert_eq!(reader.position(), 2);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"c\n");
+
+    let file = archive.open("empty.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    assert!(reader.fill_buf().unwrap().is_empty());
+    assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0);
+    reader.skip(0).unwrap();
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn reader_guarded() {
+    let archive = archive();
+    // 1MiB of the same byte compresses extremely well
+    let file = archive.open("1MiB.file").unwrap();
+    let mut reader = file.reader_guarded(10).unwrap();
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    let err = err.get_ref().unwrap().downcast_ref::<sqsh_rs::Error>();
+    assert_eq!(err.unwrap().name(), "OutOfBounds");
+
+    let mut reader = file.reader_guarded(u32::MAX).unwrap();
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents.len(), 1024 * 1024);
+    assert_eq!(reader.position(), 1024 * 1024);
+
+    // Fragments are counted as uncompressed
+    let file = archive.open("short.file").unwrap();
+    let mut contents = Vec::new();
+    file.reader_guarded(1)
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, b"abc\n");
+}
+
+#[test]
+fn reader_take() {
+    let archive = archive();
+    let file = archive.open("1MiB.file").unwrap();
+    let mut reader = file.reader().unwrap().take(200 * 1024);
+    // The first block is larger than the limit, fill_buf must not return more
+    let first_block = reader.fill_buf().unwrap().len();
+    assert!(first_block <= 200 * 1024);
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents.len(), 200 * 1024);
+    assert!(contents.iter().all(|&b| b == b'A'));
+    assert_eq!(reader.limit(), 0);
+    assert!(reader.fill_buf().unwrap().is_empty());
+
+    let mut reader = reader.into_inner();
+    assert_eq!(reader.position(), 200 * 1024);
+    assert_eq!(
+        reader.read_to_vec().unwrap().len(),
+        1024 * 1024 - 200 * 1024
+    );
+
+    // A limit past the end of the file reads the whole file
+    let file = archive.open("short.file").unwrap();
+    let mut contents = Vec::new();
+    file.reader()
+        .unwrap()
+        .take(100)
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, b"abc\n");
+}
+
+#[test]
+fn reader_read_to_vec() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    assert_eq!(reader.remaining(), 4);
+    reader.skip(1).unwrap();
+    assert_eq!(reader.remaining(), 3);
+    assert_eq!(reader.read_to_vec().unwrap(), b"bc\n");
+    assert_eq!(reader.remaining(), 0);
+    assert_eq!(reader.read_to_vec().unwrap(), b"");
+
+    let file = archive.open("1MiB.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    let mut buf = [0; 10];
+    reader.read_exact(&mut buf).unwrap();
+    let rest = reader.read_to_vec().unwrap();
+    assert_eq!(rest.len(), 1024 * 1024 - 10);
+    assert!(rest.iter().all(|&b| b == b'A'));
+}
+
+#[test]
+fn easy_read_or_default() {
+    let archive = archive();
+    assert_eq!(archive.read_or_default("short.file", Vec::new), b"abc\n");
+    assert_eq!(
+        archive.read_or_default("missing.conf", || b"default".to_vec()),
+        b"default"
+    );
+    assert_eq!(
+        archive
+            .try_read_or_default("subdir/missing.conf", || b"default".to_vec())
+            .unwrap(),
+        b"default"
+    );
+    // Only missing files use the default
+    let err = archive
+        .try_read_or_default("subdir", || b"default".to_vec())
+        .unwrap_err();
+    assert_ne!(err.name(), "NoSuchFile");
+}
+
+#[test]
+fn easy_read_or_default_error() {
+    let archive = archive();
+    // Reading a directory fails, which is replaced with the default
+    assert_eq!(
+        archive.read_or_default("subdir", || b"default".to_vec()),
+        b"default"
+    );
+}
+
+#[test]
+fn open_concat() {
+    let archive = archive();
+    // subdir contains one.file and short.file
+    let mut contents = Vec::new();
+    archive
+        .open_concat("subdir")
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, b"aabc\n");
+
+    // empty_dir has no parts, deep only contains a directory
+    let mut reader = archive.open_concat("empty_dir").unwrap();
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+    let mut reader = archive.open_concat("deep").unwrap();
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+
+    assert!(archive.open_concat("short.file").is_err());
+}
+
 #[test]
 fn easy_contents_one() {
     let archive = archive();
@@ -53,12 +515,252 @@ fn easy_contents_one() {
     assert_eq!(data, "a".as_bytes());
 }
 
+#[test]
+fn easy_contents_capped() {
+    let archive = archive();
+    let data = archive.read_capped("short.file", 4).unwrap();
+    assert_eq!(data, b"abc\n");
+    let data = archive.read_capped("empty.file", 0).unwrap();
+    assert!(data.is_empty());
+
+    let err = archive.read_capped("short.file", 3).unwrap_err();
+    insta::assert_snapshot!(err, @"Out of bounds");
+    assert!(archive.read_capped("1MiB.file", 1024).is_err());
+}
+
+#[test]
+fn easy_contents_nofollow() {
+    let archive = archive();
+    assert_eq!(archive.read_nofollow("short.link").unwrap(), b"short.file");
+    assert_eq!(
+        archive.read_nofollow("broken.link").unwrap(),
+        b"nonexistent"
+    );
+    assert_eq!(archive.read_nofollow("short.file").unwrap(), b"abc\n");
+    assert!(archive.read("broken.link").is_err());
+}
+
 #[test]
 fn easy_contents_of_directory() {
     let archive = archive();
     let err = archive.read("subdir").unwrap_err();
-    assert_eq!(err.io_error_kind(), std::io::ErrorKind::Other);
-    insta::assert_snapshot!(err, @"Not a file");
+    assert!(err.is_a_directory());
+    assert_eq!(
+        err.io_error_kind(),
+        std::io::Error::from_raw_os_error(libc::EISDIR).kind()
+    );
+    insta::assert_snapshot!(err, @"Is a directory");
+
+    let err = archive.read_dir("short.file").err().unwrap();
+    assert!(err.is_not_a_directory());
+    assert!(!err.is_a_directory());
+    assert_eq!(
+        err.io_error_kind(),
+        std::io::Error::from_raw_os_error(libc::ENOTDIR).kind()
+    );
+}
+
+#[test]
+fn easy_prefetch() {
+    let archive = archive();
+    archive.prefetch("1MiB.file").unwrap();
+    archive.prefetch("empty.file").unwrap();
+
+    let err = archive.prefetch("not_exists").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+    assert!(archive.prefetch("subdir").is_err());
+}
+
+#[test]
+fn easy_count_entries() {
+    let archive = archive();
+    assert_eq!(archive.count_entries("one.file").unwrap(), 1);
+    assert_eq!(archive.count_entries("subdir").unwrap(), 3);
+    // deep, level1 through level5, and the file
+    assert_eq!(archive.count_entries("deep").unwrap(), 7);
+    assert!(archive.count_entries("not_exists").is_err());
+}
+
+#[test]
+fn easy_entries_by_inode_order() {
+    let archive = archive();
+    let entries = archive.entries_by_inode_order().unwrap();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    let mut count = 0;
+    while let Some(entry) = traversal.advance().unwrap() {
+        if !entry.state().is_second_visit() {
+            count += 1;
+        }
+    }
+    assert_eq!(entries.len(), count);
+    assert!(entries.windows(2).all(|w| w[0].1 <= w[1].1));
+    for path in ["", "1MiB.file", "short.link", "subdir", "subdir/one.file"] {
+        let (_, inode_ref) = entries.iter().find(|(p, _)| p == path).unwrap();
+        let expected = if path.is_empty() {
+            root.inode_ref()
+        } else {
+            archive.open_nofollow(path).unwrap().inode_ref()
+        };
+        assert_eq!(*inode_ref, expected);
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn tree_json() {
+    let archive = archive();
+    let dir_permissions = archive.permissions("subdir").unwrap();
+    let file_permissions = archive.permissions("subdir/one.file").unwrap();
+    let expected = format!(
+        concat!(
+            r#"{{"name":"subdir","type":"directory","size":{},"permissions":"{}","children":["#,
+            r#"{{"name":"one.file","type":"file","size":1,"permissions":"{}"}},"#,
+            r#"{{"name":"short.file","type":"file","size":4,"permissions":"{}"}}"#,
+            "]}}",
+        ),
+        archive.open("subdir").unwrap().size(),
+        dir_permissions,
+        file_permissions,
+        archive.permissions("subdir/short.file").unwrap(),
+    );
+    assert_eq!(archive.tree_json("subdir").unwrap(), expected);
+
+    let json = archive.tree_json("").unwrap();
+    assert!(json.starts_with(r#"{"name":"","type":"directory""#));
+    assert!(json.ends_with("]}"));
+}
+
+#[test]
+fn easy_list_dir() {
+    let archive = archive();
+    let entries = archive.list_dir("subdir").unwrap();
+    let names: Vec<_> = entries.iter().map(|e| e.name()).collect();
+    assert_eq!(names, ["one.file", "short.file"]);
+    let metadata = entries[1].metadata();
+    assert_eq!(metadata.file_type(), Some(FileType::File));
+    assert_eq!(metadata.size(), 4);
+    assert_eq!(
+        *metadata,
+        archive.open("subdir/short.file").unwrap().metadata()
+    );
+
+    assert_eq!(
+        archive.list_dir("short.file").unwrap_err().name(),
+        "NotADirectory"
+    );
+}
+
+#[test]
+fn extract_to_verified() {
+    let archive = archive();
+    let dest = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("extract_to_verified");
+    let _ = std::fs::remove_dir_all(&dest);
+
+    let mut events = Vec::new();
+    archive
+        .extract_to_verified("subdir", &dest, |progress| {
+            events.push((
+                progress.path.to_owned(),
+                progress.bytes_done,
+                progress.bytes_total,
+            ));
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        [
+            (BString::from("one.file"), 1, 1),
+            (BString::from("short.file"), 4, 4),
+        ]
+    );
+    assert_eq!(std::fs::read(dest.join("one.file")).unwrap(), b"a");
+    assert_eq!(std::fs::read(dest.join("short.file")).unwrap(), b"abc\n");
+
+    // Extracting again overwrites the existing files
+    archive.extract_to_verified("", &dest, |_| {}).unwrap();
+    assert_eq!(
+        std::fs::read(dest.join("1MiB.file")).unwrap(),
+        vec![b'A'; 1024 * 1024]
+    );
+    assert_eq!(
+        std::fs::read_link(dest.join("short.link")).unwrap(),
+        std::path::Path::new("short.file")
+    );
+    assert_eq!(
+        std::fs::read(dest.join("subdir/short.file")).unwrap(),
+        b"abc\n"
+    );
+
+    let err = archive
+        .extract_to_verified("not_exists", &dest, |_| {})
+        .unwrap_err();
+    assert!(matches!(err.kind(), sqsh_rs::ExtractErrorKind::Archive(_)));
+    std::fs::remove_dir_all(&dest).unwrap();
+}
+
+#[test]
+fn extract_to_verified_existing_symlink() {
+    let archive = archive();
+    let base = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("extract_existing_symlink");
+    let _ = std::fs::remove_dir_all(&base);
+    let dest = base.join("dest");
+    let outside = base.join("outside");
+    std::fs::create_dir_all(&dest).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    std::os::unix::fs::symlink("../outside", dest.join("subdir")).unwrap();
+
+    // Writing subdir/one.file would follow the symlink out of dest
+    let err = archive.extract_to_verified("", &dest, |_| {}).unwrap_err();
+    assert_eq!(err.path(), "subdir");
+    assert!(matches!(err.kind(), sqsh_rs::ExtractErrorKind::Io(_)));
+    assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn easy_total_uncompressed_size() {
+    let archive = archive();
+    let total = archive.total_uncompressed_size().unwrap();
+    let mut expected = 0;
+    for result in archive.walk_files("").unwrap() {
+        let (_, file) = result.unwrap();
+        if file.file_type() == Some(FileType::File) {
+            expected += file.size();
+        }
+    }
+    // The fixture has no hard linked regular files
+    assert_eq!(total, expected);
+    assert!(total > archive.bytes_used());
+    assert_eq!(archive.total_uncompressed_size().unwrap(), total);
+}
+
+#[test]
+fn easy_symlink_target() {
+    let archive = archive();
+    let target = archive.symlink_target("short.link").unwrap();
+    assert_eq!(target, Some(BString::from("short.file")));
+    let target = archive.symlink_target("broken.link").unwrap();
+    assert_eq!(target, Some(BString::from("nonexistent")));
+
+    assert_eq!(archive.symlink_target("one.file").unwrap(), None);
+    let err = archive.symlink_target("not_exists").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn easy_is_dir_is_file() {
+    let archive = archive();
+    assert!(archive.is_dir("subdir"));
+    assert!(archive.is_dir(""));
+    assert!(!archive.is_dir("one.file"));
+    assert!(!archive.is_dir("not_exists"));
+
+    assert!(archive.is_file("one.file"));
+    assert!(archive.is_file("short.link"));
+    assert!(!archive.is_file("broken.link"));
+    assert!(!archive.is_file("subdir"));
+    assert!(!archive.is_file("fifo"));
 }
 
 #[test]
@@ -76,6 +778,7 @@ fn open_file() {
     let archive = archive();
     let file = archive.open("one.file").unwrap();
     assert!(!file.is_extended());
+    assert_eq!(file.xattr_id(), None);
 }
 
 #[test]
@@ -86,6 +789,136 @@ fn open_dir() {
     insta::assert_debug_snapshot!("subdir debug", dir);
 }
 
+#[test]
+fn open_child() {
+    let archive = archive();
+    let dir = archive.open("subdir").unwrap();
+    let child = dir.open_child(b"short.file").unwrap();
+    assert_eq!(
+        child.inode_ref(),
+        archive.open("subdir/short.file").unwrap().inode_ref()
+    );
+
+    let err = dir.open_child(b"not_exists").unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+
+    let file = archive.open("one.file").unwrap();
+    assert!(file.open_child(b"anything").is_err());
+}
+
+#[test]
+fn read_dir() {
+    let archive = archive();
+    let mut iter = archive.read_dir("subdir").unwrap();
+    let mut names = Vec::new();
+    while let Some(entry) = iter.advance().unwrap() {
+        names.push(entry.name().to_string());
+    }
+    assert_eq!(names, ["one.file", "short.file"]);
+
+    let mut iter = archive.read_root_dir().unwrap();
+    let first = iter.advance().unwrap().unwrap();
+    assert_eq!(first.name(), "1MiB.file");
+
+    let err = archive.read_dir("one.file").err().unwrap();
+    assert_eq!(
+        err,
+        sqsh_rs::Error::Sqsh(sqsh_rs::ffi::SqshError::SQSH_ERROR_NOT_A_DIRECTORY)
+    );
+    let err = archive.read_dir("not_exists").err().unwrap();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn directory_entry_open_symlink() {
+    let archive = archive();
+    let mut iter = archive.read_root_dir().unwrap();
+    let entry = iter.advance_lookup(b"short.link").unwrap().unwrap();
+    let mut file = entry.open().unwrap();
+    assert_eq!(file.file_type(), Some(FileType::Symlink));
+    assert_eq!(file.symlink_path().unwrap(), "short.file");
+
+    file.follow_all_symlinks().unwrap();
+    assert_eq!(file.file_type(), Some(FileType::File));
+}
+
+#[test]
+fn directory_collect_sorted() {
+    let archive = archive();
+    let entries = archive.read_root_dir().unwrap().collect_sorted().unwrap();
+    assert!(entries.windows(2).all(|w| w[0].name() < w[1].name()));
+
+    let entries = archive
+        .read_dir("subdir")
+        .unwrap()
+        .collect_sorted()
+        .unwrap();
+    let names: Vec<_> = entries.iter().map(|e| e.name().to_string()).collect();
+    assert_eq!(names, ["one.file", "short.file"]);
+    let file = archive.open_ref(entries[1].inode_ref()).unwrap();
+    assert_eq!(file.inode(), entries[1].inode());
+    assert_eq!(entries[1].file_type(), Some(FileType::File));
+}
+
+#[test]
+fn read_dir_ref() {
+    let archive = archive();
+    let inode_ref = archive.open("subdir").unwrap().inode_ref();
+    let mut iter = archive.read_dir_ref(inode_ref).unwrap();
+    let mut names = Vec::new();
+    while let Some(entry) = iter.advance().unwrap() {
+        names.push(entry.name().to_string());
+    }
+    assert_eq!(names, ["one.file", "short.file"]);
+
+    let inode_ref = archive.open("one.file").unwrap().inode_ref();
+    assert!(archive.read_dir_ref(inode_ref).is_err());
+}
+
+#[test]
+fn directory_entry_metadata() {
+    let archive = archive();
+    let dir = archive.open("subdir").unwrap();
+    let mut iter = dir.as_dir().unwrap();
+    let entry = iter.advance_lookup(b"short.file").unwrap().unwrap();
+    let metadata = entry.open_metadata().unwrap();
+    let file = entry.open().unwrap();
+
+    assert_eq!(metadata, file.metadata());
+    assert_eq!(metadata.file_type(), Some(FileType::File));
+    assert_eq!(metadata.size(), 4);
+    assert_eq!(metadata.inode(), entry.inode());
+    assert_eq!(metadata.inode_ref(), entry.inode_ref());
+    assert_eq!(metadata.uid(), file.uid());
+    assert_eq!(metadata.gid(), file.gid());
+    assert_eq!(metadata.permissions(), file.permissions());
+    assert_eq!(metadata.modified_time(), file.modified_time());
+}
+
+#[test]
+fn metadata_id_names() {
+    let archive = archive();
+    let metadata = archive.open("one.file").unwrap().metadata();
+
+    let mut names = sqsh_rs::IdNames::default();
+    assert_eq!(metadata.owner_name(&names), None);
+    assert_eq!(metadata.group_name(&names), None);
+
+    names.users.insert(metadata.uid(), "user".to_owned());
+    names.groups.insert(metadata.gid(), "group".to_owned());
+    assert_eq!(metadata.owner_name(&names), Some("user"));
+    assert_eq!(metadata.group_name(&names), Some("group"));
+}
+
+#[test]
+fn open_with_ref() {
+    let archive = archive();
+    let (file, inode_ref) = archive.open_with_ref("short.link").unwrap();
+    assert_eq!(file.inode_ref(), inode_ref);
+    assert_eq!(inode_ref, archive.open("short.file").unwrap().inode_ref());
+    assert!(archive.open_with_ref("not_exists").is_err());
+}
+
 #[test]
 fn reopen_by_id() {
     let archive = archive();
@@ -132,13 +965,146 @@ fn reader_buf_read() {
         assert!(buf.iter().all(|&b| b == b'A'));
         total_size += buf.len();
 
-        // Only consume part of the buffer, ensure that the remaining part is returned by fill_buf
-        reader.consume(2);
-        let remaining_buf = reader.fill_buf().unwrap();
-        assert_eq!(remaining_buf.len(), len - 2);
-        reader.consume(len - 2);
-    }
-    assert_eq!(total_size, 1024 * 1024);
+        // Only consume part of the buffer, ensure that the remaining part is returned by fill_buf
+        reader.consume(2);
+        let remaining_buf = reader.fill_buf().unwrap();
+        assert_eq!(remaining_buf.len(), len - 2);
+        reader.consume(len - 2);
+    }
+    assert_eq!(total_size, 1024 * 1024);
+}
+
+#[test]
+fn file_read_into() {
+    let archive = archive();
+    let mut buf = b"leftover data".to_vec();
+
+    let file = archive.open("short.file").unwrap();
+    assert_eq!(file.read_into(&mut buf).unwrap(), 4);
+    assert_eq!(buf, b"abc\n");
+
+    let file = archive.open("1MiB.file").unwrap();
+    assert_eq!(file.read_into(&mut buf).unwrap(), 1024 * 1024);
+    assert!(buf.iter().all(|&b| b == b'A'));
+
+    let file = archive.open("empty.file").unwrap();
+    assert_eq!(file.read_into(&mut buf).unwrap(), 0);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn reader_read_until() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    let mut buf = Vec::new();
+    assert_eq!(reader.read_until(b'b', &mut buf).unwrap(), 2);
+    assert_eq!(buf, b"ab");
+    buf.clear();
+    assert_eq!(reader.read_until(b'b', &mut buf).unwrap(), 2);
+    assert_eq!(buf, b"c\n");
+    assert_eq!(reader.read_until(b'b', &mut buf).unwrap(), 0);
+
+    let mut reader = file.reader().unwrap();
+    let lines: Vec<String> = reader.by_ref().lines().map(Result::unwrap).collect();
+    assert_eq!(lines, ["abc"]);
+
+    // No delimiter: reads across blocks to the end of the file
+    let file = archive.open("1MiB.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    let mut buf = Vec::new();
+    assert_eq!(reader.read_until(b'\n', &mut buf).unwrap(), 1024 * 1024);
+    assert!(buf.iter().all(|&b| b == b'A'));
+}
+
+#[test]
+fn file_lines() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let lines: Vec<BString> = file.lines().unwrap().map(Result::unwrap).collect();
+    assert_eq!(lines, [BString::from("abc")]);
+
+    // No trailing newline
+    let file = archive.open("one.file").unwrap();
+    let lines: Vec<BString> = file.lines().unwrap().map(Result::unwrap).collect();
+    assert_eq!(lines, [BString::from("a")]);
+
+    let file = archive.open("empty.file").unwrap();
+    assert_eq!(file.lines().unwrap().count(), 0);
+}
+
+#[test]
+fn reader_skip_to_next_block() {
+    let archive = archive();
+    let file = archive.open("1MiB.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    let block_size = reader.block_size();
+    assert_eq!(reader.skip_to_next_block().unwrap(), 0);
+
+    let mut buf = [0u8; 10];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(reader.bytes_consumed_in_block(), 10);
+    let skipped = reader.skip_to_next_block().unwrap();
+    assert_eq!(skipped, block_size as u64 - 10);
+    assert_eq!(reader.position(), block_size as u64);
+    assert_eq!(reader.skip_to_next_block().unwrap(), 0);
+
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(reader.bytes_consumed_in_block(), 10);
+}
+
+#[test]
+fn reader_next_nonzero_run() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    assert_eq!(reader.next_nonzero_run().unwrap(), 0);
+    assert_eq!(reader.position(), 0);
+
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).unwrap();
+    assert_eq!(reader.next_nonzero_run().unwrap(), 0);
+    assert_eq!(reader.position(), 4);
+
+    let file = archive.open("empty.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    assert_eq!(reader.next_nonzero_run().unwrap(), 0);
+}
+
+#[test]
+fn archive_file_trait() {
+    fn contents(file: &dyn sqsh_rs::ArchiveFile) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(file.size() as usize);
+        file.reader().unwrap().read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    assert_eq!(contents(&file), b"abc\n");
+    let file: &dyn sqsh_rs::ArchiveFile = &file;
+    assert_eq!(file.file_type(), Some(FileType::File));
+}
+
+#[test]
+fn reader_try_clone() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut reader = file.reader().unwrap();
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ab");
+    assert_eq!(reader.position(), 2);
+
+    let mut clone = reader.try_clone().unwrap();
+    assert_eq!(clone.position(), 2);
+    let mut rest = Vec::new();
+    clone.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"c\n");
+
+    // The original reader is unaffected
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"c\n");
 }
 
 #[test]
@@ -239,17 +1205,162 @@ fn skip_past_end() {
     let mut reader = file.reader().unwrap();
 
     assert_eq!(
-        reader.skip(1024 * 1024 + 1).unwrap_err().to_string(),
-        "Out of bounds"
+        reader.skip(1024 * 1024 + 1).unwrap_err().name(),
+        "OutOfBounds"
     );
 }
 
+#[test]
+fn lookup_and_readdir() {
+    let archive = archive();
+    let root_ref = archive.superblock().root_inode_ref();
+
+    let (subdir_ref, metadata) = archive.lookup(root_ref, b"subdir").unwrap().unwrap();
+    assert_eq!(metadata.file_type(), Some(FileType::Directory));
+    assert_eq!(subdir_ref, archive.open("subdir").unwrap().inode_ref());
+    assert!(archive.lookup(root_ref, b"missing").unwrap().is_none());
+
+    let (file_ref, metadata) = archive.lookup(subdir_ref, b"short.file").unwrap().unwrap();
+    assert_eq!(metadata.size(), 4);
+    let mut buf = [0; 4];
+    let file = archive.open_ref(file_ref).unwrap();
+    assert_eq!(file.read_at(0, &mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"abc\n");
+
+    let mut dir = archive.readdir(subdir_ref, 1).unwrap();
+    let entry = dir.advance().unwrap().unwrap();
+    assert_eq!(entry.name(), "short.file");
+    assert!(dir.advance().unwrap().is_none());
+
+    let mut dir = archive.readdir(subdir_ref, 10).unwrap();
+    assert!(dir.advance().unwrap().is_none());
+}
+
+#[test]
+fn block_count() {
+    let archive = archive();
+    let block_size = u64::from(archive.block_size());
+    let file = archive.open("1MiB.file").unwrap();
+    assert_eq!(file.block_count(), file.size().div_ceil(block_size));
+    assert_eq!(file.block_count(), 8);
+    assert!(!file.uses_fragment());
+
+    let file = archive.open("short.file").unwrap();
+    assert_eq!(file.block_count(), 0);
+    assert!(file.uses_fragment());
+
+    let file = archive.open("empty.file").unwrap();
+    assert_eq!(file.block_count(), 0);
+    assert!(!file.uses_fragment());
+
+    let dir = archive.open("subdir").unwrap();
+    assert_eq!(dir.block_count(), 0);
+    assert_eq!(dir.data_range(), None);
+}
+
+#[test]
+fn file_hash() {
+    use std::hash::Hasher;
+
+    // Records the exact bytes written, to check the whole file is streamed in order
+    #[derive(Default)]
+    struct Collect(Vec<u8>);
+
+    impl Hasher for Collect {
+        fn finish(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    let archive = archive();
+    for path in ["1MiB.file", "short.file", "empty.file"] {
+        let file = archive.open(path).unwrap();
+        let mut hasher = Collect::default();
+        file.hash(&mut hasher).unwrap();
+        assert_eq!(hasher.0, archive.read(path).unwrap());
+    }
+
+    let mut a = std::collections::hash_map::DefaultHasher::new();
+    archive.open("short.file").unwrap().hash(&mut a).unwrap();
+    let mut b = std::collections::hash_map::DefaultHasher::new();
+    archive.open("short.link").unwrap().hash(&mut b).unwrap();
+    assert_eq!(a.finish(), b.finish());
+}
+
+#[test]
+fn read_at() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    let mut buf = [0; 10];
+    assert_eq!(file.read_at(1, &mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"bc\n");
+    assert_eq!(file.read_at(4, &mut buf).unwrap(), 0);
+    assert_eq!(file.read_at(100, &mut buf).unwrap(), 0);
+
+    let file = archive.open("1MiB.file").unwrap();
+    let mut buf = vec![0; 200 * 1024];
+    assert_eq!(file.read_at(100 * 1024, &mut buf).unwrap(), buf.len());
+    assert!(buf.iter().all(|&b| b == b'A'));
+    assert_eq!(file.read_at(1024 * 1024 - 2, &mut buf).unwrap(), 2);
+}
+
 #[test]
 fn compression_options() {
     let archive = archive();
     insta::assert_debug_snapshot!(archive.compression_options());
 }
 
+#[test]
+fn compression_spec() {
+    let archive = archive();
+    let spec = archive.compression_spec().unwrap();
+    assert_eq!(spec.compression, Compression::GZIP);
+    assert_eq!(spec.block_size, 131072);
+    assert_eq!(spec.options, archive.compression_options().unwrap());
+    assert!(matches!(
+        spec.options,
+        Some(CompressionOptions::Gzip {
+            compression_level: 8,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn compression_summary_without_options() {
+    let mut data = std::fs::read(ARCHIVE_PATH).unwrap();
+    // Clear the "compressor options present" superblock flag
+    let flags = u16::from_le_bytes([data[24], data[25]]) & !0x0400;
+    data[24..26].copy_from_slice(&flags.to_le_bytes());
+    let archive = Archive::from_vec(data).unwrap();
+    assert_eq!(archive.compression_options().unwrap(), None);
+    assert_eq!(archive.compressor_name(), Some("gzip"));
+    assert_eq!(archive.effective_block_size(), 131072);
+}
+
+#[test]
+fn compression_option_flags_iter() {
+    use sqsh_rs::superblock::{GzipStrategies, XzFilters};
+
+    let strategies = GzipStrategies::DEFAULT | GzipStrategies::RLE;
+    assert_eq!(
+        strategies.iter().collect::<Vec<_>>(),
+        [GzipStrategies::DEFAULT, GzipStrategies::RLE]
+    );
+    let filters = XzFilters::X86 | XzFilters::ARM;
+    assert_eq!(
+        filters
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        ["X86", "ARM"]
+    );
+}
+
 fn traversal_str(traversal: &mut Traversal) -> String {
     let mut result = String::new();
     while let Some(entry) = traversal.advance().unwrap() {
@@ -267,6 +1378,11 @@ fn traversal_str(traversal: &mut Traversal) -> String {
             entry.path().segments().next_back().unwrap_or_default(),
             entry.name(),
         );
+        assert_eq!(entry.path_buf(), entry.path().to_string());
+        assert_eq!(entry.path().byte_len(), entry.path_buf().len());
+        assert_eq!(entry.is_dir(), entry.file_type() == FileType::Directory);
+        assert_eq!(entry.is_file(), entry.file_type() == FileType::File);
+        assert_eq!(entry.is_symlink(), entry.file_type() == FileType::Symlink);
     }
     result
 }
@@ -297,6 +1413,39 @@ fn traverse_start_file() {
     insta::assert_snapshot!(traversal_str(&mut traversal));
 }
 
+#[test]
+fn traverse_follow_symlinks() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let entries = |follow: bool| {
+        let mut traversal = root.traversal().unwrap();
+        traversal.set_follow_symlinks(follow);
+        let mut entries = Vec::new();
+        while let Some(entry) = traversal.advance().unwrap() {
+            entries.push((entry.path_buf(), entry.state()));
+        }
+        entries
+    };
+
+    // Symlinks to files and dangling symlinks are visited as normal leaves, and the symlinks to
+    // ancestors (zlinks/loop and zlinks/nested/up) are not followed. Only zlinks/dir_link, a
+    // symlink to subdir, is descended into, without visiting the target directory itself.
+    let mut expected = entries(false);
+    let dir_link = expected
+        .iter()
+        .position(|(path, _)| path == "zlinks/dir_link")
+        .unwrap();
+    assert_eq!(expected[dir_link].1, State::Normal);
+    expected.splice(
+        dir_link + 1..dir_link + 1,
+        [
+            (BString::from("zlinks/dir_link/one.file"), State::Normal),
+            (BString::from("zlinks/dir_link/short.file"), State::Normal),
+        ],
+    );
+    assert_eq!(entries(true), expected);
+}
+
 #[test]
 fn traverse_max_depth() {
     let archive = archive();
@@ -306,3 +1455,440 @@ fn traverse_max_depth() {
 
     insta::assert_snapshot!(traversal_str(&mut traversal));
 }
+
+#[test]
+fn traverse_entry_reader() {
+    let archive = archive();
+    let subdir = archive.open("subdir").unwrap();
+    let mut traversal = subdir.traversal().unwrap();
+    let mut contents = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        if entry.is_file() {
+            let mut reader = entry.reader().unwrap();
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).unwrap();
+            contents.push((entry.path_buf(), buf));
+        }
+    }
+    assert_eq!(
+        contents,
+        [
+            (BString::from("one.file"), b"a".to_vec()),
+            (BString::from("short.file"), b"abc\n".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn traverse_symlink_target() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    traversal.set_max_depth(1);
+    let mut targets = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        if let Some(target) = entry.symlink_target().unwrap() {
+            targets.push((entry.path_buf(), target));
+        } else {
+            assert!(!entry.is_symlink());
+        }
+    }
+    assert_eq!(targets.len(), 2);
+    assert_eq!(
+        targets[1],
+        (BString::from("short.link"), BString::from("short.file"))
+    );
+    assert_eq!(
+        Some(&targets[0].1),
+        archive.symlink_target("broken.link").unwrap().as_ref()
+    );
+}
+
+#[test]
+fn xattr_count() {
+    let archive = archive();
+    let subdir = archive.open("subdir").unwrap();
+    assert!(!subdir.has_xattrs());
+    assert_eq!(subdir.xattr_count().unwrap(), 0);
+
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    while let Some(entry) = traversal.advance().unwrap() {
+        let file = entry.open().unwrap();
+        let mut xattrs = file.xattrs().unwrap();
+        let mut count = 0;
+        while xattrs.advance().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(file.xattr_count().unwrap(), count);
+        if !file.has_xattrs() {
+            assert_eq!(count, 0);
+        }
+    }
+}
+
+#[test]
+fn find_with_xattr() {
+    let archive = archive();
+    assert!(archive
+        .find_with_xattr(b"user.nonexistent")
+        .unwrap()
+        .is_empty());
+
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    while let Some(entry) = traversal.advance().unwrap() {
+        if entry.state().is_second_visit() {
+            continue;
+        }
+        let file = entry.open().unwrap();
+        let mut xattrs = file.xattrs().unwrap();
+        while let Some(xattr) = xattrs.advance().unwrap() {
+            let mut full_name = BString::from(xattr.prefix());
+            full_name.extend_from_slice(xattr.name());
+            let found = archive.find_with_xattr(&full_name).unwrap();
+            assert!(found.contains(&entry.path_buf()));
+        }
+    }
+}
+
+#[test]
+fn as_dir_not_a_directory() {
+    let archive = archive();
+    let file = archive.open("short.file").unwrap();
+    assert_eq!(file.as_dir().err().unwrap().name(), "NotADirectory");
+
+    let (err, file) = file.try_into_dir().err().unwrap();
+    assert_eq!(err.name(), "NotADirectory");
+    assert_eq!(file.size(), 4);
+
+    let dir = archive.open("subdir").unwrap();
+    assert!(dir.try_into_dir().is_ok());
+}
+
+#[test]
+fn open_with_context() {
+    let archive = archive();
+    let file = archive.open_with_context("subdir/short.file").unwrap();
+    assert_eq!(file.size(), 4);
+
+    let err = archive.open_with_context("subdir/nonexistent").unwrap_err();
+    assert_eq!(err.path(), "subdir/nonexistent");
+    assert_eq!(err.error().name(), "NoSuchFile");
+    assert!(err.to_string().starts_with("\"subdir/nonexistent\": "));
+    let err: sqsh_rs::Error = err.into();
+    assert_eq!(err.name(), "NoSuchFile");
+}
+
+#[test]
+fn archive_index() {
+    let archive = archive();
+    let index = archive.index().unwrap();
+    assert_eq!(index.len(), archive.count_entries("").unwrap());
+    assert_eq!(index[0].0, "");
+    assert_eq!(index[0].1.file_type(), Some(FileType::Directory));
+
+    let (_, metadata) = index
+        .iter()
+        .find(|(path, _)| path == "subdir/short.file")
+        .unwrap();
+    assert_eq!(metadata.size(), 4);
+    let (_, metadata) = index.iter().find(|(path, _)| path == "short.link").unwrap();
+    assert_eq!(metadata.file_type(), Some(FileType::Symlink));
+    assert!(index.iter().any(|(path, _)| path == "weird/ /😭"));
+}
+
+#[test]
+fn file_at_offset() {
+    let archive = archive();
+    let file = archive.open("1MiB.file").unwrap();
+    let range = file.data_range().unwrap();
+    assert!(range.start < range.end);
+    assert!(range.end <= archive.bytes_used());
+    assert_eq!(archive.open("short.file").unwrap().data_range(), None);
+
+    assert_eq!(
+        archive.file_at_offset(range.start).unwrap().unwrap(),
+        "1MiB.file"
+    );
+    assert_eq!(
+        archive.file_at_offset(range.end - 1).unwrap().unwrap(),
+        "1MiB.file"
+    );
+    // The superblock
+    assert_eq!(archive.file_at_offset(0).unwrap(), None);
+}
+
+#[test]
+fn used_ids() {
+    let archive = archive();
+    let used = archive.used_ids().unwrap();
+    assert!(!used.is_empty());
+
+    let id_table = archive.id_table().unwrap();
+    assert_eq!(id_table.len(), usize::from(archive.superblock().id_count()));
+    let ids: Vec<u32> = id_table.iter().collect();
+    assert_eq!(ids.len(), id_table.len());
+    for (uid, gid) in used {
+        assert!(ids.contains(&uid));
+        assert!(ids.contains(&gid));
+    }
+}
+
+#[test]
+fn read_ref() {
+    let archive = archive();
+    let inode_ref = archive.path_to_ref("subdir/short.file").unwrap();
+    assert_eq!(archive.read_ref(inode_ref).unwrap(), b"abc\n");
+    let inode_ref = archive.path_to_ref("empty.file").unwrap();
+    assert_eq!(archive.read_ref(inode_ref).unwrap(), b"");
+}
+
+#[test]
+fn file_read_head() {
+    let archive = archive();
+    let file = archive.open("1MiB.file").unwrap();
+    assert_eq!(file.read_head(4).unwrap(), b"AAAA");
+    assert_eq!(file.read_head(0).unwrap(), b"");
+
+    let file = archive.open("short.file").unwrap();
+    assert_eq!(file.read_head(100).unwrap(), b"abc\n");
+    let file = archive.open("empty.file").unwrap();
+    assert_eq!(file.read_head(100).unwrap(), b"");
+}
+
+#[test]
+fn path_to_ref() {
+    let archive = archive();
+    let inode_ref = archive.path_to_ref("subdir/short.file").unwrap();
+    assert_eq!(
+        inode_ref,
+        archive.open("subdir/short.file").unwrap().inode_ref()
+    );
+    assert_eq!(
+        archive.path_to_ref("short.link").unwrap(),
+        archive.open("short.file").unwrap().inode_ref()
+    );
+    assert_eq!(
+        archive.path_to_ref("").unwrap(),
+        archive.superblock().root_inode_ref()
+    );
+    let err = archive.path_to_ref("nonexistent").unwrap_err();
+    assert_eq!(err.name(), "NoSuchFile");
+}
+
+#[test]
+fn shared_archive_reader_thread() {
+    let archive = sqsh_rs::SharedArchive::new(archive());
+    let mut reader = archive.open_reader("short.link").unwrap();
+    drop(archive);
+    let contents = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        buf
+    })
+    .join()
+    .unwrap();
+    assert_eq!(contents, b"abc\n");
+}
+
+#[test]
+fn file_into_reader() {
+    let archive = archive();
+    let mut reader = archive.open("short.file").unwrap().into_reader().unwrap();
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf).unwrap();
+    let mut clone = reader.try_clone().unwrap();
+    let mut rest = Vec::new();
+    clone.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"bc\n");
+    drop(reader);
+    assert_eq!(clone.position(), 4);
+}
+
+#[test]
+fn walk_files() {
+    let archive = archive();
+    let files: Vec<_> = archive
+        .walk_files("deep")
+        .unwrap()
+        .map(|r| {
+            let (path, file) = r.unwrap();
+            (path, file.file_type())
+        })
+        .collect();
+    assert_eq!(
+        files,
+        [(
+            BString::from("level1/level2/level3/level4/level5/file"),
+            Some(FileType::File)
+        )]
+    );
+
+    let paths: Vec<_> = archive
+        .walk_files("subdir")
+        .unwrap()
+        .include_dirs(true)
+        .map(|r| r.unwrap().0)
+        .collect();
+    assert_eq!(paths, ["", "one.file", "short.file"]);
+
+    assert!(archive.walk_files("not_exists").is_err());
+}
+
+#[test]
+fn par_for_each_file() {
+    let archive = archive();
+    let seen = std::sync::Mutex::new(Vec::new());
+    archive
+        .par_for_each_file("subdir", 3, |path, file| {
+            seen.lock()
+                .unwrap()
+                .push((BString::from(path), file.size()));
+        })
+        .unwrap();
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(
+        seen,
+        [
+            (BString::from("one.file"), 1),
+            (BString::from("short.file"), 4)
+        ]
+    );
+
+    assert!(archive
+        .par_for_each_file("not_exists", 2, |_, _| {})
+        .is_err());
+}
+
+#[test]
+fn par_for_each_file_panic() {
+    let archive = archive();
+    // A single worker, so the walk would block on a full channel if it wasn't stopped
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        archive.par_for_each_file("", 1, |_, _| panic!("callback panicked"))
+    }));
+    let panic = result.unwrap_err();
+    assert_eq!(panic.downcast_ref::<&str>(), Some(&"callback panicked"));
+}
+
+#[test]
+fn par_for_each_file_error() {
+    let mut data = std::fs::read(ARCHIVE_PATH).unwrap();
+    // Corrupt the compressed directory table, so listing any directory fails
+    let dir_table_start = u64::from_le_bytes(data[72..80].try_into().unwrap());
+    let corrupt_at = usize::try_from(dir_table_start).unwrap() + 8;
+    data[corrupt_at] ^= 0xFF;
+    let archive = Archive::from_slice(&data).unwrap();
+
+    let calls = std::sync::atomic::AtomicUsize::new(0);
+    let result = archive.par_for_each_file("", 2, |_, _| {
+        calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    });
+    assert!(result.is_err());
+    assert_eq!(calls.into_inner(), 0);
+}
+
+#[test]
+fn walk_with_progress() {
+    let archive = archive();
+    let entries: Vec<_> = archive
+        .walk_with_progress("subdir")
+        .unwrap()
+        .map(|r| {
+            let (path, _, bytes) = r.unwrap();
+            (path, bytes)
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (BString::from(""), 0),
+            (BString::from("one.file"), 1),
+            (BString::from("short.file"), 5),
+        ]
+    );
+
+    let mut walk = archive.walk_files("").unwrap().with_progress();
+    for result in walk.by_ref() {
+        result.unwrap();
+    }
+    // At least 1MiB.file, one.file, short.file, subdir/one.file and subdir/short.file
+    let total = walk.bytes_visited();
+    assert!(total >= 1024 * 1024 + 1 + 4 + 1 + 4);
+}
+
+#[test]
+fn traverse_parent_inode() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    while let Some(entry) = traversal.advance().unwrap() {
+        if entry.depth() == 0 {
+            assert_eq!(entry.parent_inode(), None);
+            continue;
+        }
+        let file = entry.open().unwrap();
+        if file.file_type() == Some(FileType::Directory) {
+            assert_eq!(entry.parent_inode(), Some(file.parent_inode()));
+        }
+        if entry.path_buf() == "subdir/one.file" {
+            let subdir = archive.open("subdir").unwrap();
+            assert_eq!(entry.parent_inode(), Some(subdir.inode()));
+        }
+    }
+}
+
+#[test]
+fn traverse_visit_directories_once() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    let mut expected = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        if !entry.state().is_second_visit() {
+            expected.push(entry.path_buf());
+        }
+    }
+
+    let mut traversal = root.traversal().unwrap();
+    traversal.set_visit_directories_once(true);
+    let mut paths = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        assert_ne!(entry.state(), State::DirectorySecond);
+        paths.push(entry.path_buf());
+    }
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn traverse_empty_dir() {
+    let archive = archive();
+    let root = archive.root().unwrap();
+    let mut traversal = root.traversal().unwrap();
+    let mut visits = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        visits.push((entry.path_buf(), entry.state()));
+    }
+    let first = visits
+        .iter()
+        .position(|(path, _)| path == "empty_dir")
+        .unwrap();
+    assert_eq!(visits[first].1, State::DirectoryFirst);
+    // Nothing between entering and leaving an empty directory
+    assert_eq!(
+        visits[first + 1],
+        ("empty_dir".into(), State::DirectorySecond)
+    );
+
+    // Starting at an empty directory visits only the directory itself, twice
+    let empty_dir = archive.open("empty_dir").unwrap();
+    let mut traversal = empty_dir.traversal().unwrap();
+    let mut states = Vec::new();
+    while let Some(entry) = traversal.advance().unwrap() {
+        assert_eq!(entry.depth(), 0);
+        states.push(entry.state());
+    }
+    assert_eq!(states, [State::DirectoryFirst, State::DirectorySecond]);
+}
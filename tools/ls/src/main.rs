@@ -2,7 +2,7 @@ use bstr::BStr;
 use clap::Parser;
 use color_eyre::eyre::{OptionExt, WrapErr};
 use sqsh_rs::traverse::Entry;
-use sqsh_rs::{Archive, FileType};
+use sqsh_rs::Archive;
 use std::fmt::Write as _;
 use std::io::{self, stdout, IsTerminal, Write as _};
 use std::path::{Path, PathBuf};
@@ -162,16 +162,7 @@ fn print_item_detail(
 
     let mut buffer = String::with_capacity(128);
     let file_type = file.file_type().ok_or_eyre("unknown file type")?;
-    let type_ch = match file_type {
-        FileType::Directory => 'd',
-        FileType::File => '-',
-        FileType::Symlink => 'l',
-        FileType::BlockDevice => 'b',
-        FileType::CharacterDevice => 'c',
-        FileType::Fifo => 'p',
-        FileType::Socket => 's',
-    };
-    buffer.push(type_ch);
+    buffer.push(file_type.type_char());
     buffer.push_str(&file.permissions().to_str());
     write!(
         buffer,